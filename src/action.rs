@@ -0,0 +1,117 @@
+/// 编辑器动作的注册表，供命令面板(<Alt+x>)列出与执行
+///
+/// 每个动作有名字、按键绑定与一行说明，执行方式为以下之一：
+/// - 聚焦到指定视图后按下绑定的按键，视图为空时由`Screen`直接处理
+/// - 向指定视图发送消息，用于没有按键绑定的菜单命令
+use getch_rs::Key;
+
+/// 动作的执行方式
+pub enum Run {
+    /// `(视图, 按键)`
+    Key(&'static str, Key),
+    /// `(视图, 消息)`
+    Msg(&'static str, &'static str),
+}
+
+pub struct Action {
+    pub name: &'static str,
+    pub binding: &'static str,
+    pub desc: &'static str,
+    pub run: Run,
+}
+
+macro_rules! actions {
+    (
+        $(
+            $name: expr, $binding: expr, $desc: expr, $run: expr,
+        )*
+    ) => {
+        /// 所有注册的动作
+        pub fn all() -> Vec<Action> {
+            vec![
+                $(
+                Action {
+                    name: $name,
+                    binding: $binding,
+                    desc: $desc,
+                    run: $run,
+                },
+                )*
+            ]
+        }
+    };
+}
+
+const MAIN: &str = "MainView";
+const TREE: &str = "FileTree";
+
+/// 带修饰键的方向键，`m`的含义见`key::decode`
+fn arrow(m: u8, code: u8) -> Key {
+    Key::Other(vec![27, b'[', b'1', b';', m, code])
+}
+
+actions! {
+    "Toggle Help", "<F1>", "open or close the help pages", Run::Key("", Key::F(1)),
+    "Focus Main View", "<F2>", "focus the text editing area", Run::Key("", Key::F(2)),
+    "Focus File Tree", "<F3>", "focus the file tree", Run::Key("", Key::F(3)),
+    "Focus Menu", "<F4>", "focus the command bar", Run::Key("", Key::F(4)),
+    "Next View", "<F5>", "focus the next view", Run::Key("", Key::F(5)),
+    "Record Macro", "<F9>", "start or stop recording a keyboard macro", Run::Key("", Key::F(9)),
+    "Play Macro", "<F10>", "play a recorded keyboard macro", Run::Key("", Key::F(10)),
+    "Quit", "<Esc>", "quit the editor, asking to save modified files", Run::Key("", Key::Esc),
+
+    "Save File", "<Ctrl+s>", "save the current file", Run::Key(MAIN, Key::Ctrl('s')),
+    "Save As", "", "save the current file to a path chosen in the file dialog", Run::Msg(MAIN, "saveas"),
+    "Open File", "<Ctrl+o>", "open a file from the file dialog", Run::Key(MAIN, Key::Ctrl('o')),
+    "Find File", "<Ctrl+p>", "fuzzy find a file in the workspace", Run::Key(MAIN, Key::Ctrl('p')),
+    "Close File", "<Ctrl+w>", "close the current file", Run::Key(MAIN, Key::Ctrl('w')),
+    "Close Other Files", "", "close every file but the current one", Run::Msg(MAIN, "closeothers"),
+    "Close All Files", "", "close every file", Run::Msg(MAIN, "closeall"),
+    "Next File", "<F6>", "switch to the next file", Run::Key(MAIN, Key::F(6)),
+    "Previous File", "<F7>", "switch to the previous file", Run::Key(MAIN, Key::F(7)),
+    "Switch File", "<F8>", "choose a file from the open files", Run::Key(MAIN, Key::F(8)),
+
+    "Tree: New File", "<a>", "create a file in the selected directory", Run::Key(TREE, Key::Char('a')),
+    "Tree: New Directory", "<A>", "create a directory in the selected directory", Run::Key(TREE, Key::Char('A')),
+    "Tree: Rename", "<r>", "rename the selected file or directory", Run::Key(TREE, Key::Char('r')),
+    "Tree: Move", "<m>", "move the selected file or directory", Run::Key(TREE, Key::Char('m')),
+    "Tree: Copy", "<c>", "copy the selected file or directory", Run::Key(TREE, Key::Char('c')),
+    "Tree: Duplicate", "<d>", "duplicate the selected file or directory", Run::Key(TREE, Key::Char('d')),
+    "Tree: Move to Trash", "<Delete>", "move the selected file or directory to the trash", Run::Key(TREE, Key::Delete),
+    "Tree: Filter", "</>", "show only files whose names contain the input", Run::Key(TREE, Key::Char('/')),
+    "Tree: Toggle Hidden Files", "<H>", "show or hide files starting with a dot", Run::Key(TREE, Key::Char('H')),
+    "Tree: Toggle Ignored Files", "<I>", "show or hide files ignored by .gitignore and .ignore", Run::Key(TREE, Key::Char('I')),
+    "Tree: Cycle Sort", "<s>", "sort by name, size, modified time or extension", Run::Key(TREE, Key::Char('s')),
+    "Tree: Reveal Current File", "<f>", "expand the tree to the current file and select it", Run::Key(TREE, Key::Char('f')),
+
+    "Undo", "<Ctrl+z>", "undo the last edit", Run::Key(MAIN, Key::Ctrl('z')),
+    "Redo", "<Ctrl+y>", "redo the last undone edit", Run::Key(MAIN, Key::Ctrl('y')),
+    "Copy", "<Ctrl+c>", "copy the selection or the line", Run::Key(MAIN, Key::Ctrl('c')),
+    "Cut", "<Ctrl+x>", "cut the selection or the line", Run::Key(MAIN, Key::Ctrl('x')),
+    "Paste", "<Ctrl+v>", "paste at every caret", Run::Key(MAIN, Key::Ctrl('v')),
+    "Toggle Overwrite", "<Insert>", "switch between insert and overwrite mode", Run::Key(MAIN, Key::Insert),
+    "Search", "<Ctrl+f>", "search, press again to replace", Run::Key(MAIN, Key::Ctrl('f')),
+    "Go to Position", "<Ctrl+g>", "go to a line, column or percentage", Run::Key(MAIN, Key::Ctrl('g')),
+    "Jump to Bracket", "<Ctrl+]>", "jump to the matching bracket", Run::Key(MAIN, Key::Ctrl('5')),
+
+    "Select Next Match", "<Ctrl+d>", "select the word or add a caret at its next match", Run::Key(MAIN, Key::Ctrl('d')),
+    "Select All Matches", "<Alt+l>", "add carets at every match of the last search", Run::Key(MAIN, Key::Alt('l')),
+    "Add Caret Above", "<Ctrl+Alt+Up>", "add a caret on the line above", Run::Key(MAIN, arrow(b'7', b'A')),
+    "Add Caret Below", "<Ctrl+Alt+Down>", "add a caret on the line below", Run::Key(MAIN, arrow(b'7', b'B')),
+    "Clear Carets", "<Alt+c>", "keep only the main caret", Run::Key(MAIN, Key::Alt('c')),
+
+    "Duplicate Lines", "<Alt+d>", "duplicate the line or the selected lines", Run::Key(MAIN, Key::Alt('d')),
+    "Delete Lines", "<Ctrl+k>", "delete the line or the selected lines", Run::Key(MAIN, Key::Ctrl('k')),
+    "Move Lines Up", "<Alt+Up>", "move the line or the selected lines up", Run::Key(MAIN, arrow(b'3', b'A')),
+    "Move Lines Down", "<Alt+Down>", "move the line or the selected lines down", Run::Key(MAIN, arrow(b'3', b'B')),
+    "Join Lines", "<Alt+j>", "join the selected lines or the next line", Run::Key(MAIN, Key::Alt('j')),
+    "Sort Lines", "<Alt+s>", "sort the selected lines ascending", Run::Key(MAIN, Key::Alt('s')),
+    "Sort Lines Descending", "<Alt+S>", "sort the selected lines descending", Run::Key(MAIN, Key::Alt('S')),
+    "Sort Lines Numerically", "<Alt+n>", "sort the selected lines by their leading number", Run::Key(MAIN, Key::Alt('n')),
+    "Unique Lines", "<Alt+u>", "remove duplicated lines from the selection", Run::Key(MAIN, Key::Alt('u')),
+    "Reverse Lines", "<Alt+r>", "reverse the order of the selected lines", Run::Key(MAIN, Key::Alt('r')),
+    "Shuffle Lines", "<Alt+h>", "shuffle the selected lines randomly", Run::Key(MAIN, Key::Alt('h')),
+
+    "Shrink Main View", "<Alt+Left>", "make the main view narrower", Run::Key(MAIN, arrow(b'3', b'D')),
+    "Grow Main View", "<Alt+Right>", "make the main view wider", Run::Key(MAIN, arrow(b'3', b'C')),
+}
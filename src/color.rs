@@ -2,7 +2,7 @@ use widestring::Utf16String;
 
 pub const END: &str = "\x1b[0m";
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct Color {
     r: u8,
     g: u8,
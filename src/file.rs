@@ -1,6 +1,6 @@
 use std::cell::RefCell;
 use std::collections::hash_map::DefaultHasher;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::fs::{self, File, Metadata, OpenOptions};
 use std::hash::{Hash, Hasher};
 use std::io::{self, BufReader, Read, Seek, Write};
@@ -14,58 +14,6 @@ pub(crate) type FileID = usize;
 /// 通过`Rc`与`RefCell`复合实现`Content`的多所有者的功能，便于与`Screen`前端对接
 pub(crate) type Content = Rc<RefCell<Vec<Utf16String>>>;
 
-impl From<&PathBuf> for FileBuf {
-    fn from(value: &PathBuf) -> Self {
-        let file: Option<File>;
-        let metadata: Option<Metadata>;
-        let mut open_options = OpenOptions::new();
-        let mut buf = String::new();
-        let pathbuf: PathBuf;
-
-        if !value.try_exists().unwrap() {
-            file = None;
-            metadata = None;
-            pathbuf = PathBuf::new();
-        } else {
-            let md = fs::metadata(value).unwrap();
-            let open_options = if md.permissions().readonly() {
-                open_options.read(true).write(false)
-            } else {
-                open_options.read(true).write(true)
-            };
-
-            let f = open_options.open(value).unwrap();
-
-            let mut buf_reader = BufReader::new(&f);
-            buf_reader.read_to_string(&mut buf).unwrap();
-
-            pathbuf = fs::canonicalize(value).unwrap();
-            metadata = Some(md);
-            file = Some(f);
-        };
-        let name: String = pathbuf.file_name().unwrap().to_str().unwrap().to_string();
-
-        let content = buf
-            .split('\n')
-            .map(|s| s.into())
-            .collect::<Vec<Utf16String>>();
-        let content = Rc::new(RefCell::new(content));
-        let copies = buf.into_bytes();
-
-        FileBuf {
-            name,
-            file,
-            dirty: false,
-            pos: (0, 0),
-            scroll: 0,
-            pathbuf,
-            metadata,
-            content,
-            copies,
-        }
-    }
-}
-
 /// 描述单个文件的结构体
 /// 文件可以不存在，此时在保存时会自动创建新文件
 /// 若没有名字，会进行询问
@@ -84,10 +32,135 @@ pub struct FileBuf {
     content: Content,
     /// 备份，用于检查是否有修改
     copies: Vec<u8>,
+    /// 撤销/重做历史
+    history: History,
+}
+
+/// 编辑的种类，连续的同类编辑会被合并为一步撤销
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditKind {
+    Insert,
+    Delete,
+    Other,
+}
+
+/// 一步编辑：从第`start`行起，把`old`中的行替换为`new`中的行
+#[derive(Debug, Clone)]
+struct Delta {
+    start: usize,
+    old: Vec<Utf16String>,
+    new: Vec<Utf16String>,
+    /// 撤销或重做后恢复的光标位置`(行, 下标)`
+    pos: (usize, usize),
+}
+
+impl Delta {
+    /// 从`old`到`new`的变化，不含首尾相同的行，没有变化时返回`None`
+    fn diff(old: &[Utf16String], new: &[Utf16String], pos: (usize, usize)) -> Option<Self> {
+        let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+        if prefix == old.len() && prefix == new.len() {
+            return None;
+        }
+        let rest = old.len().min(new.len()) - prefix;
+        let suffix = old
+            .iter()
+            .rev()
+            .zip(new.iter().rev())
+            .take(rest)
+            .take_while(|(a, b)| a == b)
+            .count();
+        Some(Delta {
+            start: prefix,
+            old: old[prefix..old.len() - suffix].to_vec(),
+            new: new[prefix..new.len() - suffix].to_vec(),
+            pos,
+        })
+    }
+
+    fn apply(&self, lines: &mut Vec<Utf16String>) {
+        let end = self.start + self.old.len();
+        lines.splice(self.start..end, self.new.iter().cloned());
+    }
+
+    fn revert(&self, lines: &mut Vec<Utf16String>) {
+        let end = self.start + self.new.len();
+        lines.splice(self.start..end, self.old.iter().cloned());
+    }
+
+    /// 合并紧接着的编辑`next`，`lines`为两次编辑之间的内容
+    fn merge(&mut self, next: &Delta, lines: &[Utf16String]) {
+        let (end, next_end) = (self.start + self.new.len(), next.start + next.old.len());
+        let (start, stop) = (self.start.min(next.start), end.max(next_end));
+
+        let mut old = lines[start..self.start].to_vec();
+        old.append(&mut self.old);
+        old.extend_from_slice(&lines[end..stop]);
+        let mut new = lines[start..next.start].to_vec();
+        new.extend_from_slice(&next.new);
+        new.extend_from_slice(&lines[next_end..stop]);
+
+        self.start = start;
+        self.old = old;
+        self.new = new;
+    }
+
+    /// 占用的字节数
+    fn size(&self) -> usize {
+        let units: usize = self
+            .old
+            .iter()
+            .chain(&self.new)
+            .map(|line| line.len())
+            .sum();
+        units * 2 + (self.old.len() + self.new.len()) * std::mem::size_of::<Utf16String>()
+    }
+}
+
+/// 以增量形式保存的编辑历史
+///
+/// `saved`为最近一次记录时的内容，每次编辑后与当前内容比较，
+/// 只把变化的行压入撤销栈
+#[derive(Debug, Default)]
+struct History {
+    undo: VecDeque<Delta>,
+    redo: Vec<Delta>,
+    saved: Vec<Utf16String>,
+    last: Option<EditKind>,
+    /// 撤销栈占用的字节数
+    size: usize,
+}
+
+/// 撤销栈的最大步数
+const HISTORY_MAX: usize = 500;
+/// 撤销栈最多占用的字节数，超过时丢弃最早的编辑，但至少保留一步
+const HISTORY_BYTES: usize = 64 << 20;
+
+impl History {
+    fn new(content: &[Utf16String]) -> Self {
+        History {
+            saved: content.to_vec(),
+            ..Default::default()
+        }
+    }
+
+    fn push(&mut self, delta: Delta) {
+        self.size += delta.size();
+        self.undo.push_back(delta);
+        while self.undo.len() > HISTORY_MAX || (self.size > HISTORY_BYTES && self.undo.len() > 1) {
+            let dropped = self.undo.pop_front().unwrap();
+            self.size -= dropped.size();
+        }
+    }
+
+    fn pop(&mut self) -> Option<Delta> {
+        let delta = self.undo.pop_back()?;
+        self.size -= delta.size();
+        Some(delta)
+    }
 }
 
 impl FileBuf {
-    fn new(input: String) -> Result<Self, Box<dyn std::error::Error>> {
+    fn new(input: String) -> io::Result<Self> {
         let path = Path::new(&input);
         let file: Option<File>;
         let metadata: Option<Metadata>;
@@ -124,6 +197,7 @@ impl FileBuf {
             .split('\n')
             .map(|s| s.into())
             .collect::<Vec<Utf16String>>();
+        let history = History::new(&content);
         let content = Rc::new(RefCell::new(content));
         let copies = buf.into_bytes();
 
@@ -137,6 +211,7 @@ impl FileBuf {
             metadata,
             content,
             copies,
+            history,
         })
     }
 
@@ -150,6 +225,12 @@ impl FileBuf {
         &self.pathbuf
     }
 
+    /// 文件扩展名，用于判断语言
+    #[inline]
+    pub fn extension(&self) -> Option<&str> {
+        Path::new(&self.name).extension()?.to_str()
+    }
+
     #[inline]
     pub fn try_open(&mut self) -> io::Result<()> {
         let file = OpenOptions::new().read(true).write(true).open(&self.name)?;
@@ -194,10 +275,10 @@ impl FileBuf {
                 .split('\n')
                 .map(|s| s.into())
                 .collect::<Vec<Utf16String>>();
-            let content = Rc::new(RefCell::new(content));
             let copies = buf.into_bytes();
 
-            self.content = content;
+            // 原地替换，保证与主视图共享的`Content`不失效
+            *self.content.borrow_mut() = content;
             self.copies = copies;
         }
         Ok(())
@@ -207,7 +288,19 @@ impl FileBuf {
         &self.content
     }
 
-    fn save(&mut self) -> io::Result<()> {
+    /// 用文本替换内容，替换后视为没有修改
+    fn set_text(&mut self, text: String) {
+        let content = text
+            .split('\n')
+            .map(|s| s.into())
+            .collect::<Vec<Utf16String>>();
+        self.history = History::new(&content);
+        *self.content.borrow_mut() = content;
+        self.copies = text.into_bytes();
+    }
+
+    /// 保存文件，没有名字的新文件保存为`dir`中的`a.txt`
+    fn save(&mut self, dir: &Path) -> io::Result<()> {
         let content = self.flatten();
         match self.file {
             Some(ref mut file) => {
@@ -223,10 +316,14 @@ impl FileBuf {
             }
             None => {
                 if self.name.is_empty() {
-                    self.name = String::from("a.txt");
+                    self.name = dir.join("a.txt").to_string_lossy().into_owned();
                 }
                 let mut open_options = OpenOptions::new();
-                let open_options = open_options.read(true).write(true).create(true);
+                let open_options = open_options
+                    .read(true)
+                    .write(true)
+                    .create(true)
+                    .truncate(true);
                 let mut file = open_options.open(&self.name)?;
                 file.write_all(&content)?;
                 self.copies = content.clone();
@@ -257,6 +354,58 @@ impl FileBuf {
         (x, y, self.scroll)
     }
 
+    /// 在编辑后调用，若内容有变化则记录一步撤销
+    ///
+    /// `pos`为编辑前的光标位置，连续的同类`Insert`/`Delete`编辑会被合并
+    pub fn record(&mut self, pos: (usize, usize), kind: EditKind) {
+        let content = self.content.borrow();
+        let history = &mut self.history;
+        let delta = match Delta::diff(&history.saved, &content, pos) {
+            Some(delta) => delta,
+            None => {
+                history.last = None;
+                return;
+            }
+        };
+        let is_merge = kind != EditKind::Other && history.last == Some(kind);
+        let last = if is_merge { history.pop() } else { None };
+        match last {
+            Some(mut last) => {
+                last.merge(&delta, &history.saved);
+                delta.apply(&mut history.saved);
+                history.push(last);
+            }
+            None => {
+                delta.apply(&mut history.saved);
+                history.push(delta);
+            }
+        }
+        history.redo.clear();
+        history.last = Some(kind);
+    }
+
+    /// 撤销一步，返回应恢复的光标位置
+    pub fn undo(&mut self, pos: (usize, usize)) -> Option<(usize, usize)> {
+        let delta = self.history.pop()?;
+        delta.revert(&mut self.content.borrow_mut());
+        delta.revert(&mut self.history.saved);
+        self.history.last = None;
+        let prev = delta.pos;
+        self.history.redo.push(Delta { pos, ..delta });
+        Some(prev)
+    }
+
+    /// 重做一步，返回应恢复的光标位置
+    pub fn redo(&mut self, pos: (usize, usize)) -> Option<(usize, usize)> {
+        let delta = self.history.redo.pop()?;
+        delta.apply(&mut self.content.borrow_mut());
+        delta.apply(&mut self.history.saved);
+        self.history.last = None;
+        let next = delta.pos;
+        self.history.push(Delta { pos, ..delta });
+        Some(next)
+    }
+
     #[inline]
     pub fn is_dirty(&self) -> bool {
         self.dirty
@@ -282,6 +431,12 @@ pub struct FileMod {
     file_cnt: FileID,
     curr_file: Option<FileID>,
     curr_dir: PathBuf,
+    /// 工作区的根目录，只有一个时与`curr_dir`相同
+    roots: Vec<PathBuf>,
+    /// 切换到过的文件，最近使用的在前
+    recent: Vec<FileID>,
+    /// 管道模式下退出时写入标准输出的文件
+    pipe: Option<FileID>,
 }
 
 impl FileMod {
@@ -293,12 +448,33 @@ impl FileMod {
             file_map,
             file_cnt: 2,
             curr_file: Some(1),
+            roots: vec![curr_dir.clone()],
+            recent: vec![1],
             curr_dir,
+            pipe: None,
         }
     }
 
     pub fn set_dir(&mut self, dir: PathBuf) {
         self.curr_dir = fs::canonicalize(dir).unwrap();
+        self.roots = vec![self.curr_dir.clone()];
+    }
+
+    /// 只改变当前目录，工作区的根目录保持不变
+    pub fn change_dir(&mut self, dir: &Path) -> io::Result<()> {
+        self.curr_dir = fs::canonicalize(dir)?;
+        Ok(())
+    }
+
+    /// 设置工作区的根目录，第一个目录作为当前目录
+    pub fn set_roots(&mut self, dirs: Vec<PathBuf>) {
+        self.roots = dirs
+            .into_iter()
+            .map(|dir| fs::canonicalize(dir).unwrap())
+            .collect();
+        if let Some(dir) = self.roots.first() {
+            self.curr_dir = dir.clone();
+        }
     }
 
     /// 通过计算哈希并与备份的哈希值比较来记录是否被修改
@@ -334,17 +510,102 @@ impl FileMod {
         &self.curr_dir
     }
 
-    pub fn insert_from_path(&mut self, path: &PathBuf) -> FileID {
-        let cnt = self.file_cnt;
-        let file_buf = FileBuf::from(path);
-        let id = self.search(path);
-        if id == 0 {
-            self.file_map.insert(cnt, file_buf);
-            self.file_cnt += 1;
-            self.file_cnt - 2
+    pub fn roots(&self) -> &[PathBuf] {
+        if self.roots.is_empty() {
+            std::slice::from_ref(&self.curr_dir)
         } else {
-            id - 1
+            &self.roots
+        }
+    }
+
+    /// 打开文件并返回编号，已经打开时返回原来的编号
+    pub fn insert_from_path(&mut self, path: &PathBuf) -> io::Result<FileID> {
+        let id = self.search(path);
+        if id != 0 {
+            return Ok(id);
         }
+        let file_buf = FileBuf::new(path.to_string_lossy().into_owned())?;
+        Ok(self.insert(file_buf))
+    }
+
+    /// 打开`path`处的文件并返回编号，文件不存在时在保存时创建
+    ///
+    /// `path`为目录、无法读取或不是UTF-8文本时返回错误
+    pub fn open(&mut self, path: &Path) -> io::Result<FileID> {
+        if path.is_dir() {
+            return Err(io::Error::new(
+                io::ErrorKind::IsADirectory,
+                "Is a Directory",
+            ));
+        }
+        self.insert_from_path(&path.to_path_buf())
+    }
+
+    /// 新建一个没有名字的文件，内容为`text`
+    pub fn open_text(&mut self, text: String) -> FileID {
+        let mut file_buf = FileBuf::new(String::new()).unwrap();
+        file_buf.set_text(text);
+        self.insert(file_buf)
+    }
+
+    fn insert(&mut self, file_buf: FileBuf) -> FileID {
+        let id = self.file_cnt;
+        self.file_map.insert(id, file_buf);
+        self.file_cnt += 1;
+        id
+    }
+
+    /// 按顺序排列的所有文件编号，关闭文件后编号之间可能有空缺
+    pub fn ids(&self) -> Vec<FileID> {
+        let mut ids: Vec<FileID> = self.file_map.keys().copied().collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    pub fn get(&self, id: FileID) -> Option<&FileBuf> {
+        self.file_map.get(&id)
+    }
+
+    /// 所有文件编号，最近使用的在前，没有切换到过的按编号排在最后
+    pub fn recent(&self) -> Vec<FileID> {
+        let mut ids = self.recent.clone();
+        ids.extend(
+            self.ids()
+                .into_iter()
+                .filter(|id| !self.recent.contains(id)),
+        );
+        ids
+    }
+
+    /// 把当前文件记为最近使用
+    fn touch(&mut self) {
+        if let Some(id) = self.curr_file {
+            self.recent.retain(|&other| other != id);
+            self.recent.insert(0, id);
+        }
+    }
+
+    /// 关闭文件，其余文件的编号不变
+    ///
+    /// 关闭当前文件时切换到后一个（没有时为前一个）文件，
+    /// 关闭最后一个文件时新建一个空文件
+    pub fn close(&mut self, id: FileID) {
+        if self.file_map.remove(&id).is_none() {
+            return;
+        }
+        if self.pipe == Some(id) {
+            self.pipe = None;
+        }
+        self.recent.retain(|&other| other != id);
+        if self.file_map.is_empty() {
+            let id = self.insert(FileBuf::new(String::new()).unwrap());
+            self.curr_file = Some(id);
+        } else if self.curr_file == Some(id) {
+            let ids = self.ids();
+            let next = ids.iter().find(|&&other| other > id).or(ids.last());
+            self.curr_file = next.copied();
+        }
+        self.touch();
     }
 
     pub fn search(&mut self, path: &PathBuf) -> FileID {
@@ -356,6 +617,34 @@ impl FileMod {
         0
     }
 
+    /// 文件或目录从`from`移动到`to`后，更新其中已打开的文件的路径与名字
+    pub fn moved(&mut self, from: &Path, to: &Path) {
+        for file in self.file_map.values_mut() {
+            let path = match file.pathbuf.strip_prefix(from) {
+                Ok(rest) if rest.as_os_str().is_empty() => to.to_path_buf(),
+                Ok(rest) => to.join(rest),
+                Err(_) => continue,
+            };
+            if let Some(name) = path.file_name() {
+                file.name = name.to_string_lossy().into_owned();
+            }
+            file.pathbuf = path;
+        }
+    }
+
+    /// 文件或目录`path`被删除后，其中已打开的文件不再关联磁盘上的文件，
+    /// 视为有修改，保存时在原来的路径重新创建
+    pub fn removed(&mut self, path: &Path) {
+        for file in self.file_map.values_mut() {
+            if file.file.is_some() && file.pathbuf.starts_with(path) {
+                file.file = None;
+                file.metadata = None;
+                file.name = file.pathbuf.to_string_lossy().into_owned();
+                file.copies.clear();
+            }
+        }
+    }
+
     pub fn mut_curr(&mut self) -> &mut FileBuf {
         let curr = self.curr_file.unwrap();
         self.file_map.get_mut(&curr).unwrap()
@@ -370,53 +659,135 @@ impl FileMod {
         self.curr().content()
     }
 
+    /// 当前文件的`(下标, 行, 滚动)`
+    #[inline]
+    pub fn curr_status(&self) -> (usize, usize, usize) {
+        self.curr().get_status()
+    }
+
+    /// 设置第`index`个文件（从0开始）的初始位置，行与下标会被限制在内容范围内
+    pub fn set_status(&mut self, index: usize, (line, idx): (usize, usize), scroll: usize) {
+        if let Some(file) = self.file_map.get_mut(&(index + 1)) {
+            let content = file.content.borrow();
+            let line = line.min(content.len() - 1);
+            let idx = idx.min(content[line].len());
+            drop(content);
+            file.save_status((idx, line), scroll.min(line));
+        }
+    }
+
     pub fn save(&mut self) -> io::Result<()> {
-        self.mut_curr().save()?;
+        let dir = self.curr_dir.clone();
+        self.mut_curr().save(&dir)?;
+        Ok(())
+    }
+
+    /// 保存编号为`id`的文件，给出`name`时另存为该文件，之后编辑的是新文件
+    ///
+    /// 相对的`name`以当前目录为起点，而不是进程的工作目录
+    pub fn save_file(&mut self, id: FileID, name: Option<String>) -> io::Result<()> {
+        let name = name.map(|name| self.curr_dir.join(name).to_string_lossy().into_owned());
+        if let Some(file) = self.file_map.get_mut(&id) {
+            if let Some(name) = name {
+                file.name = name;
+                file.file = None;
+            }
+            file.save(&self.curr_dir)?;
+        }
         Ok(())
     }
 
+    /// 把当前文件的内容写入`path`，当前文件不变
+    pub fn write_to(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.curr().flatten())
+    }
+
+    /// 从磁盘重新读取当前文件，放弃未保存的修改（可以撤销）
+    pub fn reload(&mut self) -> io::Result<()> {
+        let file = self.mut_curr();
+        if file.file.is_none() {
+            return Err(io::ErrorKind::NotFound.into());
+        }
+        file.sync()?;
+        file.dirty = false;
+        Ok(())
+    }
+
+    /// 记录当前文件的位置
+    pub fn save_status(&mut self, pos: (usize, usize), scroll: usize) {
+        self.mut_curr().save_status(pos, scroll);
+    }
+
     pub fn is_all_saved(&self) -> bool {
         self.file_map
-            .values()
-            .fold(true, |flag, file| !file.is_dirty() && flag)
+            .iter()
+            .filter(|(id, file)| !self.is_pipe_only(**id, file))
+            .fold(true, |flag, (_, file)| !file.is_dirty() && flag)
     }
 
     pub fn save_all(&mut self) -> io::Result<()> {
-        for file in self.file_map.values_mut() {
-            file.save()?
+        let pipe = self.pipe;
+        for (id, file) in self.file_map.iter_mut() {
+            // 只写入管道的文件与没有修改过的新文件无需保存
+            if file.file.is_none() && (pipe == Some(*id) || !file.dirty) {
+                continue;
+            }
+            file.save(&self.curr_dir)?
         }
         Ok(())
     }
 
-    pub fn name(&self) -> &String {
-        &self.curr().name
+    /// 是否为没有对应文件、只写入管道的文件
+    #[inline]
+    fn is_pipe_only(&self, id: FileID, file: &FileBuf) -> bool {
+        self.pipe == Some(id) && file.file.is_none()
     }
 
-    pub fn set_name(&mut self, name: String) {
-        self.mut_curr().name = name;
+    /// 用文本替换第`index`个文件（从0开始）的内容，用于读取标准输入
+    pub fn set_text(&mut self, index: usize, text: String) {
+        if let Some(file) = self.file_map.get_mut(&(index + 1)) {
+            file.set_text(text);
+        }
+    }
+
+    /// 设置管道模式下退出时写入标准输出的文件
+    pub fn set_pipe(&mut self, index: usize) {
+        self.pipe = Some(index + 1);
+    }
+
+    /// 管道模式下应写入标准输出的内容
+    pub fn pipe_output(&self) -> Option<Vec<u8>> {
+        let file = self.file_map.get(&self.pipe?)?;
+        Some(file.flatten())
+    }
+
+    pub fn name(&self) -> &String {
+        &self.curr().name
     }
 
     #[inline]
     pub fn shift(&mut self, pos: (usize, usize), scroll: usize) -> (usize, usize, usize) {
         self.mut_curr().save_status(pos, scroll);
-        let mut curr_file = self.curr_file.unwrap();
-        curr_file = curr_file % (self.file_cnt - 1) + 1;
-        self.curr_file = Some(curr_file);
-        self.file_map.get(&curr_file).unwrap().get_status()
+        let curr_file = self.curr_file.unwrap();
+        let ids = self.ids();
+        let next = ids.iter().find(|&&id| id > curr_file).unwrap_or(&ids[0]);
+        self.curr_file = Some(*next);
+        self.touch();
+        self.curr().get_status()
     }
 
     #[inline]
     pub fn rshift(&mut self, pos: (usize, usize), scroll: usize) -> (usize, usize, usize) {
         self.mut_curr().save_status(pos, scroll);
-        let mut curr_file = self.curr_file.unwrap();
-        curr_file -= 1;
-        if curr_file == 0 {
-            curr_file += self.file_cnt - 1;
-        }
-        self.curr_file = Some(curr_file);
-        self.file_map.get(&curr_file).unwrap().get_status()
+        let curr_file = self.curr_file.unwrap();
+        let ids = self.ids();
+        let prev = ids.iter().rev().find(|&&id| id < curr_file);
+        self.curr_file = prev.or(ids.last()).copied();
+        self.touch();
+        self.curr().get_status()
     }
 
+    /// 切换到编号为`file_id`的文件，文件不存在时不切换
     #[inline]
     pub fn shift_to(
         &mut self,
@@ -425,9 +796,11 @@ impl FileMod {
         scroll: usize,
     ) -> (usize, usize, usize) {
         self.mut_curr().save_status(pos, scroll);
-        let id = file_id % (self.file_cnt - 1) + 1;
-        self.curr_file = Some(id);
-        self.file_map.get(&id).unwrap().get_status()
+        if self.file_map.contains_key(&file_id) {
+            self.curr_file = Some(file_id);
+            self.touch();
+        }
+        self.curr().get_status()
     }
 }
 
@@ -447,6 +820,9 @@ impl From<Vec<String>> for FileMod {
             file_cnt,
             curr_file: Some(1),
             curr_dir,
+            roots: Vec::new(),
+            recent: vec![1],
+            pipe: None,
         }
     }
 }
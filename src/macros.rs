@@ -0,0 +1,133 @@
+/// 键盘宏的录制、回放与保存
+///
+/// 录制的是发送给聚焦视图`matchar`的按键（以及弹窗中的输入），
+/// <F9>开始/结束录制，<F10>回放
+///
+/// 宏保存在配置目录下的`macros`文件中，每行一个宏：
+/// ```txt
+/// name: 1b5b41 61 7f
+/// ```
+/// 每个按键以其终端字节的十六进制表示，按键之间以空格分隔
+use crate::settings::config_dir;
+use crate::terminal::key;
+use getch_rs::Key;
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// 未命名时使用的宏名
+pub const DEFAULT_SLOT: &str = "default";
+
+#[derive(Debug, Default)]
+pub struct Macros {
+    /// 正在录制的按键
+    recording: Option<Vec<Key>>,
+    /// 等待回放的按键
+    replay: VecDeque<Key>,
+    /// 已命名的宏
+    slots: HashMap<String, Vec<Key>>,
+}
+
+impl Macros {
+    /// 从配置目录读取已保存的宏
+    pub fn new() -> Self {
+        let mut macros = Macros::default();
+        let content = Macros::path().and_then(|path| fs::read_to_string(path).ok());
+        for line in content.unwrap_or_default().lines() {
+            if let Some((name, keys)) = line.split_once(": ") {
+                macros.slots.insert(name.to_string(), decode(keys));
+            }
+        }
+        macros
+    }
+
+    #[inline]
+    fn path() -> Option<PathBuf> {
+        Some(config_dir()?.join("macros"))
+    }
+
+    #[inline]
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    pub fn start(&mut self) {
+        self.recording = Some(Vec::new());
+    }
+
+    /// 结束录制，返回录制的按键
+    pub fn stop(&mut self) -> Vec<Key> {
+        self.recording.take().unwrap_or_default()
+    }
+
+    /// 录制按键，回放中的按键不会被录制
+    pub fn record(&mut self, key: &Key) {
+        if let Some(keys) = &mut self.recording {
+            keys.push(key.clone());
+        }
+    }
+
+    /// 保存宏并写入配置目录，名字为空时使用默认名
+    pub fn store(&mut self, name: &str, keys: Vec<Key>) -> io::Result<()> {
+        let name = if name.is_empty() { DEFAULT_SLOT } else { name };
+        self.slots.insert(name.to_string(), keys);
+        self.save()
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let path = Macros::path().ok_or(io::ErrorKind::NotFound)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut names: Vec<&String> = self.slots.keys().collect();
+        names.sort();
+        let content: String = names
+            .into_iter()
+            .map(|name| format!("{name}: {}\n", encode(&self.slots[name])))
+            .collect();
+        fs::write(path, content)
+    }
+
+    #[inline]
+    pub fn get(&self, name: &str) -> Option<&Vec<Key>> {
+        let name = if name.is_empty() { DEFAULT_SLOT } else { name };
+        self.slots.get(name)
+    }
+
+    /// 把宏的按键加入回放队列
+    pub fn queue(&mut self, keys: &[Key]) {
+        self.replay.extend(keys.iter().cloned());
+    }
+
+    /// 取出下一个回放的按键
+    #[inline]
+    pub fn pop(&mut self) -> Option<Key> {
+        self.replay.pop_front()
+    }
+}
+
+fn encode(keys: &[Key]) -> String {
+    keys.iter()
+        .map(|key| {
+            key::encode(key)
+                .iter()
+                .map(|byte| format!("{byte:02x}"))
+                .collect::<String>()
+        })
+        .filter(|hex| !hex.is_empty())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn decode(keys: &str) -> Vec<Key> {
+    keys.split_whitespace()
+        .filter_map(|hex| {
+            let bytes = (0..hex.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+                .collect::<Option<Vec<u8>>>()?;
+            key::parse(&bytes).0.into_iter().next()
+        })
+        .collect()
+}
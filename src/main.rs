@@ -1,47 +1,125 @@
 use clap::Parser;
-use crossbeam_channel::{bounded, select, Receiver};
+use crossbeam_channel::{bounded, never, select, Receiver};
 use file::FileMod;
-use getch_rs::{Getch, Key};
+use getch_rs::Key;
 use screen::{Module, Screen};
 use settings::Settings;
 use std::{
-    io::{self, IsTerminal},
-    path::Path,
+    io::{self, IsTerminal, Read, Write},
+    mem,
+    path::{Path, PathBuf},
     thread,
 };
-use terminal::term::Term;
+use terminal::{
+    key::KeyReader,
+    term::{MouseGuard, Term},
+    tty,
+};
 use view::msgbox::MsgBox;
+use watcher::Watcher;
 
 use signal_hook::consts::signal::*;
 use signal_hook::iterator::Signals;
 
+mod action;
 mod color;
 mod file;
+mod macros;
 mod prelude;
 mod screen;
 mod settings;
 mod terminal;
 mod view;
+mod watcher;
 
 #[derive(Parser)]
 #[command(version = "0.1.0",author = "NazrinDuck", about, long_about = None)]
 pub struct Args {
-    /// 文件路径（不能包含目录）
+    /// 文件路径，可以写作`file:line[:col]`，或在前面加上`+N`指定行；
+    /// `-`表示读取标准输入；目录作为文件树的根，多个目录组成工作区
     #[arg(value_name = "FILE")]
     pub files_name: Vec<String>,
 
+    /// 管道模式：退出时把内容写入标准输出，标准输入不是终端时读取标准输入
+    #[arg(short = 'p', long = "pipe")]
+    pub is_pipe: bool,
+
     /// 工作目录
     #[arg(short = 'd', long = "dir", value_name = "DIR", default_value_t = String::from("."))]
     pub dir: String,
+
+    /// 文件树中排除的文件，格式与`.gitignore`中的规则相同，可以多次使用
+    #[arg(short = 'x', long = "exclude", value_name = "GLOB")]
+    pub exclude: Vec<String>,
+}
+
+/// 解析文件参数，返回文件名与可选的`(行, 列)`（从1开始）
+///
+/// - `file:line[:col]`：编译器与`git grep -n`的输出格式，文件本身存在时按原样处理
+/// - `+N file`：vim风格，指定下一个文件的行号
+fn parse_files(args: Vec<String>) -> Vec<(String, Option<(usize, usize)>)> {
+    let mut files = Vec::new();
+    let mut line = None;
+    for arg in args {
+        if let Some(num) = arg.strip_prefix('+').and_then(|n| n.parse::<usize>().ok()) {
+            line = Some(num);
+            continue;
+        }
+        let (name, pos) = split_pos(&arg);
+        let pos = pos.or(line.take().map(|line| (line, 1)));
+        files.push((name, pos));
+    }
+    files
+}
+
+/// 拆分`file:line[:col]`，文件本身存在或后缀不是数字时不拆分
+fn split_pos(arg: &str) -> (String, Option<(usize, usize)>) {
+    if Path::new(arg).exists() {
+        return (arg.to_string(), None);
+    }
+    // 编译器输出的位置后可能带有冒号
+    let trimmed = arg.trim_end_matches(':');
+    let num = |s: &str| s.parse::<usize>().ok();
+
+    let parts: Vec<&str> = trimmed.rsplitn(3, ':').collect();
+    if let [col, line, name] = parts[..] {
+        if let (Some(line), Some(col), false) = (num(line), num(col), name.is_empty()) {
+            return (name.to_string(), Some((line, col)));
+        }
+    }
+    match trimmed.rsplit_once(':') {
+        Some((name, line)) if !name.is_empty() => match num(line) {
+            Some(line) => (name.to_string(), Some((line, 1))),
+            None => (arg.to_string(), None),
+        },
+        _ => (arg.to_string(), None),
+    }
 }
 
 /// 用线程接收键盘事件
 fn key_channel() -> Receiver<Key> {
-    let ch = Getch::new();
+    let mut reader = KeyReader::new();
     let (sender, receiver) = bounded(500);
+    // 读到EOF或读取出错时结束线程，主循环随之退出；
+    // 此时终端已不可用，不再恢复终端模式
     thread::spawn(move || loop {
-        let key = ch.getch().unwrap();
-        sender.send(key).unwrap();
+        let keys = match reader.read() {
+            Ok(keys) => keys,
+            Err(_) => {
+                mem::forget(reader);
+                return;
+            }
+        };
+        let is_eof = keys.contains(&Key::EOF);
+        for key in keys {
+            if sender.send(key).is_err() {
+                return;
+            }
+        }
+        if is_eof {
+            mem::forget(reader);
+            return;
+        }
     });
     receiver
 }
@@ -74,42 +152,88 @@ fn term_channel() -> Receiver<Term> {
 /// ```
 /// `Screen`负责处理主要逻辑，`main`函数负责监听事件
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // 解析参数
+    let args = Args::parse();
+    // 目录作为文件树的根，其余的作为文件打开
+    let (dirs, mut files): (Vec<_>, Vec<_>) = parse_files(args.files_name)
+        .into_iter()
+        .partition(|(name, _)| Path::new(name).is_dir());
+    let dirs: Vec<PathBuf> = dirs.into_iter().map(|(name, _)| name.into()).collect();
+
+    // 读取标准输入，之后改用控制终端读取键盘
+    let is_piped = !io::stdin().is_terminal();
+    if args.is_pipe && is_piped && files.iter().all(|(name, _)| name != "-") {
+        files.insert(0, (String::from("-"), None));
+    }
+    let stdin_idx = files.iter().position(|(name, _)| name == "-");
+    let mut stdin_text = String::new();
+    if stdin_idx.is_some() {
+        io::stdin().read_to_string(&mut stdin_text)?;
+        tty::to_tty(tty::STDIN)?;
+    }
+
+    // 管道模式下界面输出到控制终端，原来的标准输出留到退出时写入
+    let mut output = None;
+    if args.is_pipe {
+        output = Some(tty::keep(tty::STDOUT)?);
+        tty::to_tty(tty::STDOUT)?;
+    }
+
     // 检测是否在终端中
     if !io::stdout().is_terminal() {
         return Err("Please use in terminal/tty".into());
     }
 
-    // 解析参数
-    let args = Args::parse();
-
     let mut file_mod: FileMod;
     let mut term = Term::new();
-    let settings = Settings::default();
+    let mut settings = Settings::default();
     let mut screen = Screen::new();
 
-    let files_name = args.files_name;
+    term.init();
+    settings.exclude = args.exclude;
+    // 标准输入对应一个没有名字的文件
+    let files_name: Vec<String> = files
+        .iter()
+        .map(|(name, _)| {
+            if name == "-" {
+                String::new()
+            } else {
+                name.clone()
+            }
+        })
+        .collect();
     if files_name.is_empty() {
         file_mod = FileMod::new(args.dir.into());
     } else {
-        if files_name.iter().fold(false, |flag, file| {
-            let path = Path::new(file);
-            if path.is_dir() {
-                true
-            } else {
-                flag
-            }
-        }) {
-            return Err("Please don't include directory in [FILE]".into());
-        };
         file_mod = FileMod::from(files_name);
         file_mod.set_dir(args.dir.into());
     }
+    if !dirs.is_empty() {
+        file_mod.set_roots(dirs);
+    }
+
+    if let Some(index) = stdin_idx {
+        file_mod.set_text(index, stdin_text);
+    }
+    if args.is_pipe {
+        file_mod.set_pipe(stdin_idx.unwrap_or(0));
+    }
+
+    // 设置文件的初始位置，使目标行位于主视图中部
+    let half = (term.height as usize).saturating_sub(5) / 2;
+    for (index, (_, pos)) in files.iter().enumerate() {
+        if let Some((line, col)) = pos {
+            let (line, col) = (line.saturating_sub(1), col.saturating_sub(1));
+            file_mod.set_status(index, (line, col), line.saturating_sub(half));
+        }
+    }
 
     let key_events = key_channel();
     let term_events = term_channel();
+    let (watcher, mut watch_events) = Watcher::new();
 
-    term.init();
-    let mut module = Module::new(term, file_mod, settings, key_events.clone());
+    let mouse = MouseGuard::enable();
+    let mut module = Module::new(term, file_mod, settings, watcher, key_events.clone());
 
     // 初始化
     screen.init(&mut module)?;
@@ -117,11 +241,20 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     screen.update(&mut module)?;
 
     // 监听各种事件
+    let mut is_hangup = false;
     loop {
         // start interact
         select! {
             recv(key_events) -> key => {
-                if screen.interact(&mut module, key?)? {
+                // 终端已关闭，无法继续交互
+                let key = match key {
+                    Ok(Key::EOF) | Err(_) => {
+                        is_hangup = true;
+                        break;
+                    }
+                    Ok(key) => key,
+                };
+                if screen.interact(&mut module, key)? {
                     break;
                 };
             }
@@ -131,11 +264,38 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 module.term = term?;
                 screen.update(&mut module)?;
             }
+
+            // 文件树中展开的目录发生变化，一次操作产生的多个事件合并处理
+            recv(watch_events) -> path => {
+                match path {
+                    Ok(path) => {
+                        module.watcher.push(path);
+                        for path in watch_events.try_iter() {
+                            module.watcher.push(path);
+                        }
+                        screen.update(&mut module)?;
+                    }
+                    // 读取事件的线程出错退出后不再监听，编辑器继续运行
+                    Err(_) => watch_events = never(),
+                }
+            }
         };
     }
 
-    // 结束清理
-    Screen::clean(&module.term)?;
+    // 结束清理，终端已关闭时无法也无需恢复
+    if is_hangup {
+        mem::forget(mouse);
+    } else {
+        drop(mouse);
+        Screen::clean(&module.term)?;
+    }
+
+    // 管道模式下把内容写入原来的标准输出
+    if let Some(mut output) = output {
+        if let Some(text) = module.file_mod.pipe_output() {
+            output.write_all(&text)?;
+        }
+    }
 
     Ok(())
 }
@@ -4,7 +4,11 @@ pub use crate::{
     file::FileMod,
     screen::{Module, Op},
     settings::Settings,
-    terminal::{cursor::Cursor, term::Term},
+    terminal::{
+        cursor::Cursor,
+        key::{self, Mods},
+        term::Term,
+    },
     view::{Pos, Position, View, ViewID},
 };
 pub use std::io::{self, Write};
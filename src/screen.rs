@@ -1,15 +1,24 @@
 use crate::{
+    action::{self, Run},
     color::END,
     file::FileMod,
+    macros::Macros,
     settings::Settings,
     terminal::{cursor::Cursor, term::Term},
-    view::{help::Help, msgbox::MsgBox, Position},
+    view::{
+        help::Help,
+        msgbox::{Answer, MsgBox},
+        picker::{Item, Picker},
+        Position,
+    },
+    watcher::Watcher,
 };
-use crossbeam_channel::Receiver;
+use crossbeam_channel::{Receiver, RecvTimeoutError};
 use getch_rs::Key;
 use std::{
     collections::{HashMap, VecDeque},
     io::{self, stdout, Write},
+    time::Duration,
 };
 
 use crate::view::{
@@ -25,6 +34,11 @@ use crate::view::{
 /// <F3>: 聚焦至文件树
 /// <F4>: 聚焦至菜单
 /// <F5>: 顺序切换视图
+///
+/// <F9>: 开始/结束录制键盘宏
+/// <F10>: 回放键盘宏
+///
+/// <Alt+x>: 打开命令面板，模糊查找并执行注册的动作
 pub struct Screen {
     focus: ViewID,
     id_cnt: u64,
@@ -37,6 +51,8 @@ pub enum Op {
     Nothing,
     Shift(String),
     Resize(String, (i16, i16, i16, i16)),
+    /// 重新初始化所有视图，用于应用新的主题或工作目录
+    Init,
     Quit,
 }
 
@@ -45,6 +61,9 @@ pub struct Module {
     pub term: Term,
     pub file_mod: FileMod,
     pub settings: Settings,
+    pub macros: Macros,
+    /// 文件树中展开的目录的监听器
+    pub watcher: Watcher,
     pub curr_view: String,
     message: HashMap<String, VecDeque<String>>,
     operation: Vec<Op>,
@@ -57,12 +76,15 @@ impl Module {
         term: Term,
         file_mod: FileMod,
         settings: Settings,
+        watcher: Watcher,
         key_recv: Receiver<Key>,
     ) -> Module {
         Module {
             term,
             file_mod,
             settings,
+            macros: Macros::new(),
+            watcher,
             curr_view: String::new(),
             message: HashMap::new(),
             operation: Vec::new(),
@@ -94,12 +116,41 @@ impl Module {
         }
     }
 
+    /// `name`处是否有未读的信息
+    pub fn has_msg(&self, name: &String) -> bool {
+        self.message
+            .get(name)
+            .is_some_and(|queue| !queue.is_empty())
+    }
+
     pub fn push_op(&mut self, op: Op) {
         self.operation.push(op);
     }
 
-    pub fn key_channel(&self) -> Receiver<Key> {
-        self.key_recv.clone()
+    /// 读取下一个按键，回放键盘宏时优先取出宏中的按键
+    ///
+    /// 从键盘读取的按键会被录制，终端关闭后返回`Key::EOF`
+    pub fn next_key(&mut self) -> Key {
+        if let Some(key) = self.macros.pop() {
+            return key;
+        }
+        let key = self.key_recv.recv().unwrap_or(Key::EOF);
+        self.macros.record(&key);
+        key
+    }
+
+    /// 与`next_key`相同，但最多等待`timeout`，超时返回`None`
+    pub fn next_key_timeout(&mut self, timeout: Duration) -> Option<Key> {
+        if let Some(key) = self.macros.pop() {
+            return Some(key);
+        }
+        let key = match self.key_recv.recv_timeout(timeout) {
+            Ok(key) => key,
+            Err(RecvTimeoutError::Timeout) => return None,
+            Err(RecvTimeoutError::Disconnected) => Key::EOF,
+        };
+        self.macros.record(&key);
+        Some(key)
     }
 }
 
@@ -123,6 +174,9 @@ impl Screen {
 
         module.settings.num_offset = 6;
         module.settings.is_show_num = true;
+        module.settings.indent_width = 4;
+        module.settings.is_auto_pair = true;
+        module.settings.is_show_details = true;
         module.curr_view = main_view.get_name().clone();
 
         self.register(Box::new(main_view));
@@ -189,7 +243,11 @@ impl Screen {
             }
         }
 
+        // 其他视图在更新时发给聚焦视图的信息，在本次绘制前处理
         let main_view = self.view_map.get_mut(&self.focus).unwrap();
+        if module.has_msg(&module.curr_view) {
+            main_view.update(module);
+        }
         main_view.draw(module)?;
         main_view.set_cursor(module);
 
@@ -203,26 +261,31 @@ impl Screen {
             // press ESC to leave
             Key::Esc => {
                 if !module.file_mod.is_all_saved() {
-                    let ret = MsgBox::new()
-                        .title("Save All?(y/n)")
+                    let answer = MsgBox::new()
+                        .title("Save All?")
                         .default_pos(module)
-                        .wait::<String>(module)
-                        .unwrap_or_default();
-                    match &ret[..] {
-                        "n" | "N" => (),
-                        _ => module.file_mod.save_all()?,
-                    };
-                    return Ok(true);
+                        .confirm(module);
+                    match answer {
+                        Answer::Yes => match module.file_mod.save_all() {
+                            Ok(()) => return Ok(true),
+                            Err(err) => {
+                                MsgBox::new()
+                                    .title("Error")
+                                    .default_pos(module)
+                                    .show(module, &format!("Can't Save: {err}"));
+                            }
+                        },
+                        Answer::No => return Ok(true),
+                        Answer::Cancel => (),
+                    }
                 } else {
-                    let ret = MsgBox::new()
-                        .title("Quit?(y/n)")
+                    let answer = MsgBox::new()
+                        .title("Quit?")
                         .default_pos(module)
-                        .wait::<String>(module)
-                        .unwrap_or_default();
-                    match &ret[..] {
-                        "n" | "N" => (),
-                        _ => return Ok(true),
-                    };
+                        .confirm(module);
+                    if answer == Answer::Yes {
+                        return Ok(true);
+                    }
                 }
             }
 
@@ -256,8 +319,102 @@ impl Screen {
                 }
             }
 
+            Key::F(9) => {
+                if module.macros.is_recording() {
+                    let keys = module.macros.stop();
+                    let name = MsgBox::new()
+                        .title("Macro Name")
+                        .default_pos(module)
+                        .ask::<String>(module);
+                    // 取消时丢弃录制的按键
+                    let msg = match name {
+                        Some(Ok(name)) => {
+                            let name = name.split_whitespace().next().unwrap_or_default();
+                            match module.macros.store(name, keys) {
+                                Ok(()) => format!("Macro \"{name}\" Saved"),
+                                Err(err) => format!("Can't Save Macro: {err}"),
+                            }
+                        }
+                        _ => String::from("Macro Discarded"),
+                    };
+                    module.sendmsg(String::from("Menu"), msg);
+                } else {
+                    module.macros.start();
+                    module.sendmsg(String::from("Menu"), String::from("Recording Macro"));
+                }
+            }
+            Key::F(10) if module.macros.is_recording() => {
+                module.sendmsg(String::from("Menu"), String::from("Stop Recording First"));
+            }
+            Key::F(10) => {
+                let fields = MsgBox::new()
+                    .title("Play Macro")
+                    .default_pos(module)
+                    .form(module, &[("Name", ""), ("Count", "1")]);
+                let play = match fields.as_deref() {
+                    Some([name, count]) => match count.trim().parse::<usize>() {
+                        Ok(count) => Some((name.trim().to_string(), count)),
+                        Err(_) => {
+                            module.sendmsg(String::from("Menu"), String::from("Invalid Count"));
+                            None
+                        }
+                    },
+                    _ => None,
+                };
+                if let Some((name, count)) = play {
+                    match module.macros.get(&name).cloned() {
+                        Some(keys) => {
+                            for _ in 0..count {
+                                module.macros.queue(&keys);
+                                while let Some(key) = module.macros.pop() {
+                                    main_view.matchar(module, key);
+                                }
+                            }
+                        }
+                        None => {
+                            let msg = format!("Unknown Macro \"{name}\"");
+                            module.sendmsg(String::from("Menu"), msg);
+                        }
+                    }
+                }
+            }
+
+            Key::Alt('x') => {
+                let actions = action::all();
+                let items = actions
+                    .iter()
+                    .map(|action| {
+                        let label = format!("{}: {}", action.name, action.desc);
+                        Item::new(label, action.binding.to_string(), 0)
+                    })
+                    .collect();
+                let item = Picker::new()
+                    .title("Command Palette")
+                    .items(items)
+                    .default_pos(module)
+                    .wait(module);
+                let action = item.and_then(|item| {
+                    actions
+                        .into_iter()
+                        .find(|action| item.label.starts_with(&format!("{}: ", action.name)))
+                });
+                match action.map(|action| action.run) {
+                    Some(Run::Key(view, key)) => {
+                        if !view.is_empty() {
+                            module.curr_view = self.shift_to(&String::from(view)).clone();
+                        }
+                        return self.interact(module, key);
+                    }
+                    Some(Run::Msg(view, msg)) => {
+                        module.sendmsg(String::from(view), String::from(msg));
+                    }
+                    None => (),
+                }
+            }
+
             // measure input key
             key => {
+                module.macros.record(&key);
                 main_view.matchar(module, key);
             }
         }
@@ -277,6 +434,11 @@ impl Screen {
                     let view = self.view_map.get_mut(id).unwrap();
                     view.resize(&module.term, dx_s, dy_s, dx_e, dy_e);
                 }
+                Op::Init => {
+                    for view in self.view_map.values_mut() {
+                        view.init(module);
+                    }
+                }
                 Op::Quit => return Ok(true),
             }
         }
@@ -1,4 +1,5 @@
 use crate::color::Color;
+use std::path::PathBuf;
 
 #[allow(unused)]
 pub struct Theme {
@@ -40,6 +41,17 @@ impl Default for Theme {
 
 #[allow(unused)]
 impl Theme {
+    /// 可以通过`theme`命令切换的主题
+    pub const NAMES: [&'static str; 2] = ["tokyonight", "duskfox"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "tokyonight" => Some(Theme::tokyonight()),
+            "duskfox" => Some(Theme::duskfox()),
+            _ => None,
+        }
+    }
+
     /// 参考：https://github.com/EdenEast/nightfox.nvim/
     pub fn duskfox() -> Self {
         Theme {
@@ -141,9 +153,153 @@ impl Theme {
     }
 }
 
+/// 文件树的排序方式，目录总是在文件之前
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortBy {
+    /// 按名字的自然顺序，`a2`在`a10`之前
+    #[default]
+    Name,
+    /// 大的在前
+    Size,
+    /// 最近修改的在前
+    Time,
+    /// 按扩展名，相同时按名字
+    Ext,
+}
+
+impl SortBy {
+    pub const NAMES: [&'static str; 4] = ["name", "size", "time", "ext"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "name" => Some(SortBy::Name),
+            "size" => Some(SortBy::Size),
+            "time" => Some(SortBy::Time),
+            "ext" => Some(SortBy::Ext),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SortBy::Name => "name",
+            SortBy::Size => "size",
+            SortBy::Time => "time",
+            SortBy::Ext => "ext",
+        }
+    }
+
+    /// 下一种排序方式，用于循环切换
+    pub fn next(&self) -> Self {
+        match self {
+            SortBy::Name => SortBy::Size,
+            SortBy::Size => SortBy::Time,
+            SortBy::Time => SortBy::Ext,
+            SortBy::Ext => SortBy::Name,
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct Settings {
     pub theme: Theme,
     pub is_show_num: bool,
     pub num_offset: u16,
+    /// 每级缩进的空格数
+    pub indent_width: usize,
+    /// 是否自动补全括号与引号
+    pub is_auto_pair: bool,
+    /// 文件树是否显示以`.`开头的文件
+    pub is_show_hidden: bool,
+    /// 文件树是否显示被`.gitignore`与`.ignore`忽略的文件
+    pub is_show_ignored: bool,
+    /// 文件树中排除的文件，格式与`.gitignore`中的规则相同
+    pub exclude: Vec<String>,
+    /// 文件树的排序方式
+    pub sort_by: SortBy,
+    /// 文件树排序时是否区分大小写
+    pub is_sort_case: bool,
+    /// 文件树足够宽时是否显示权限、大小与修改时间
+    pub is_show_details: bool,
+    /// 切换文件时文件树是否自动定位到当前文件
+    pub is_follow: bool,
+}
+
+impl Settings {
+    /// 可以通过`set`命令修改的选项
+    pub const OPTIONS: [&'static str; 10] = [
+        "indent", "autopair", "number", "hidden", "ignored", "exclude", "sort", "sortcase",
+        "details", "follow",
+    ];
+
+    /// 选项的当前值
+    pub fn get(&self, option: &str) -> Option<String> {
+        let value = match option {
+            "indent" => self.indent_width.to_string(),
+            "autopair" => on_off(self.is_auto_pair),
+            "number" => on_off(self.is_show_num),
+            "hidden" => on_off(self.is_show_hidden),
+            "ignored" => on_off(self.is_show_ignored),
+            "exclude" => self.exclude.join(","),
+            "sort" => self.sort_by.name().to_string(),
+            "sortcase" => on_off(self.is_sort_case),
+            "details" => on_off(self.is_show_details),
+            "follow" => on_off(self.is_follow),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    /// 修改选项，失败时返回原因
+    pub fn set(&mut self, option: &str, value: &str) -> Result<(), String> {
+        match option {
+            "indent" => match value.parse::<usize>() {
+                Ok(width @ 1..=16) => self.indent_width = width,
+                _ => return Err(format!("Invalid Indent Width: `{value}`")),
+            },
+            "autopair" => self.is_auto_pair = parse_bool(value)?,
+            "number" => self.is_show_num = parse_bool(value)?,
+            "hidden" => self.is_show_hidden = parse_bool(value)?,
+            "ignored" => self.is_show_ignored = parse_bool(value)?,
+            // 以`,`分隔多个规则
+            "exclude" => {
+                self.exclude = value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|glob| !glob.is_empty())
+                    .map(String::from)
+                    .collect()
+            }
+            "sort" => match SortBy::from_name(value) {
+                Some(sort_by) => self.sort_by = sort_by,
+                None => return Err(format!("Expect name/size/time/ext: `{value}`")),
+            },
+            "sortcase" => self.is_sort_case = parse_bool(value)?,
+            "details" => self.is_show_details = parse_bool(value)?,
+            "follow" => self.is_follow = parse_bool(value)?,
+            _ => return Err(format!("Unknown Option: `{option}`")),
+        }
+        Ok(())
+    }
+}
+
+fn on_off(value: bool) -> String {
+    String::from(if value { "on" } else { "off" })
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value {
+        "on" | "true" | "yes" | "1" => Ok(true),
+        "off" | "false" | "no" | "0" => Ok(false),
+        _ => Err(format!("Expect on/off: `{value}`")),
+    }
+}
+
+/// 配置目录：`$XDG_CONFIG_HOME/tged`，未设置时为`~/.config/tged`
+pub fn config_dir() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join("tged"))
 }
@@ -0,0 +1,327 @@
+/// 按键读取与解码
+///
+/// `getch_rs`会把退格（`0x7f`）与Delete键（`ESC [ 3 ~`）都解析为`Key::Delete`，
+/// 因此`KeyReader`自行解析字节流，只借用`Getch`把终端设为原始模式：
+/// - `0x7f`：`Key::Backspace`
+/// - `ESC [ 3 ~`：`Key::Delete`
+/// - `0x08`：`Key::Ctrl('h')`，多数终端中<Ctrl+Backspace>也发送该字节
+/// - `0x00`：`Key::Ctrl(' ')`，<Ctrl+Space>与<Ctrl+@>发送该字节
+///
+/// 无法直接表示的转义序列会被完整地放入`Key::Other`，
+/// 再由`decode`把xterm风格的序列还原为基础按键与修饰键
+///
+/// - `ESC [ 1 ; m X`：方向键/Home/End，`X`为`A`~`D`/`H`/`F`
+/// - `ESC [ n ; m ~`：Insert/Delete/PageUp/PageDown，`n`为`2`/`3`/`5`/`6`
+///
+/// 其中`m - 1`为修饰键的位掩码：Shift = 1，Alt = 2，Ctrl = 4
+///
+/// 鼠标事件同样以`Key::Other`传递，由`mouse`解码
+use getch_rs::{Getch, Key};
+use std::io::{self, Read};
+
+/// 修饰键
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Mods(u8);
+
+#[allow(unused)]
+impl Mods {
+    pub const NONE: Mods = Mods(0);
+    pub const SHIFT: Mods = Mods(1);
+    pub const ALT: Mods = Mods(2);
+    pub const ALT_SHIFT: Mods = Mods(3);
+    pub const CTRL: Mods = Mods(4);
+    pub const CTRL_SHIFT: Mods = Mods(5);
+    pub const CTRL_ALT: Mods = Mods(6);
+
+    /// 去除Shift后的修饰键
+    #[inline]
+    pub fn without_shift(self) -> Mods {
+        Mods(self.0 & !1)
+    }
+
+    #[inline]
+    pub fn shift(&self) -> bool {
+        self.0 & 1 != 0
+    }
+
+    #[inline]
+    pub fn alt(&self) -> bool {
+        self.0 & 2 != 0
+    }
+
+    #[inline]
+    pub fn ctrl(&self) -> bool {
+        self.0 & 4 != 0
+    }
+}
+
+/// 把带修饰键的转义序列解码为`(基础按键, 修饰键)`
+///
+/// 无法识别的序列返回`None`
+pub fn decode(key: &Key) -> Option<(Key, Mods)> {
+    let bytes = match key {
+        Key::Other(bytes) => bytes,
+        _ => return None,
+    };
+    match bytes[..] {
+        [27, b'[', b'1', b';', m @ b'2'..=b'8', code] => {
+            let key = match code {
+                b'A' => Key::Up,
+                b'B' => Key::Down,
+                b'C' => Key::Right,
+                b'D' => Key::Left,
+                b'H' => Key::Home,
+                b'F' => Key::End,
+                _ => return None,
+            };
+            Some((key, Mods(m - b'1')))
+        }
+        [27, b'[', n, b';', m @ b'2'..=b'8', b'~'] => {
+            let key = match n {
+                b'2' => Key::Insert,
+                b'3' => Key::Delete,
+                b'5' => Key::PageUp,
+                b'6' => Key::PageDown,
+                _ => return None,
+            };
+            Some((key, Mods(m - b'1')))
+        }
+        _ => None,
+    }
+}
+
+/// 把按键编码为终端发送的字节，`parse`可以把结果还原为同一个按键
+///
+/// 用于保存键盘宏，`Key::EOF`编码为空
+pub fn encode(key: &Key) -> Vec<u8> {
+    let seq: &[u8] = match key {
+        Key::Char(ch) | Key::Alt(ch) => {
+            let mut bytes = if matches!(key, Key::Alt(_)) {
+                vec![27]
+            } else {
+                Vec::new()
+            };
+            bytes.extend_from_slice(ch.encode_utf8(&mut [0; 4]).as_bytes());
+            return bytes;
+        }
+        Key::Ctrl(ch @ 'a'..='z') => return vec![*ch as u8 - b'a' + 1],
+        Key::Ctrl(ch @ '4'..='7') => return vec![*ch as u8 - b'4' + 0x1c],
+        Key::F(n @ 1..=4) => return vec![27, b'O', b'P' + n - 1],
+        Key::F(n @ 5..=12) => {
+            let code = match n {
+                5 => 15,
+                6..=10 => n + 11,
+                _ => n + 12,
+            };
+            return format!("\x1b[{code}~").into_bytes();
+        }
+        Key::Other(bytes) => return bytes.clone(),
+        Key::Backspace => &[0x7f],
+        Key::Delete => b"\x1b[3~",
+        Key::Insert => b"\x1b[2~",
+        Key::PageUp => b"\x1b[5~",
+        Key::PageDown => b"\x1b[6~",
+        Key::Esc => &[27],
+        Key::Up => b"\x1b[A",
+        Key::Down => b"\x1b[B",
+        Key::Right => b"\x1b[C",
+        Key::Left => b"\x1b[D",
+        Key::Home => b"\x1b[H",
+        Key::End => b"\x1b[F",
+        Key::BackTab => b"\x1b[Z",
+        _ => &[],
+    };
+    seq.to_vec()
+}
+
+/// 鼠标事件的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseKind {
+    Press,
+    Drag,
+    Release,
+}
+
+/// 鼠标左键事件，坐标从1开始
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mouse {
+    pub kind: MouseKind,
+    pub x: u16,
+    pub y: u16,
+    pub mods: Mods,
+}
+
+/// 解码SGR格式的鼠标事件`ESC [ < b ; x ; y M`（松开时结尾为`m`）
+///
+/// `b`的低两位为按键，`32`表示拖动，`4`/`8`/`16`分别为Shift/Alt/Ctrl；
+/// 只处理左键，其他事件返回`None`
+pub fn mouse(key: &Key) -> Option<Mouse> {
+    let bytes = match key {
+        Key::Other(bytes) => bytes,
+        _ => return None,
+    };
+    let (&code, body) = bytes.strip_prefix(b"\x1b[<")?.split_last()?;
+    let mut params = std::str::from_utf8(body).ok()?.split(';');
+    let mut next = || params.next()?.parse::<u16>().ok();
+    let (b, x, y) = (next()?, next()?, next()?);
+
+    let kind = match (b & !0b11100, code) {
+        (0, b'M') => MouseKind::Press,
+        (32, b'M') => MouseKind::Drag,
+        (0, b'm') => MouseKind::Release,
+        _ => return None,
+    };
+    let mods = Mods(((b >> 2) & 0b111) as u8);
+    Some(Mouse { kind, x, y, mods })
+}
+
+/// 从标准输入读取按键
+pub struct KeyReader {
+    /// 仅用于设置终端模式
+    _getch: Getch,
+    /// 尚未组成完整按键的字节
+    pending: Vec<u8>,
+}
+
+impl KeyReader {
+    pub fn new() -> Self {
+        KeyReader {
+            _getch: Getch::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    /// 阻塞读取，返回这次读到的所有完整按键
+    ///
+    /// 粘贴等情况下一次会读到多个按键，标准输入关闭（如终端被挂断）时返回`Key::EOF`
+    pub fn read(&mut self) -> io::Result<Vec<Key>> {
+        let mut buf = [0u8; 1024];
+        loop {
+            let len = io::stdin().read(&mut buf)?;
+            if len == 0 {
+                return Ok(vec![Key::EOF]);
+            }
+            self.pending.extend_from_slice(&buf[..len]);
+            let (keys, used) = parse(&self.pending);
+            self.pending.drain(..used);
+            if !keys.is_empty() {
+                return Ok(keys);
+            }
+        }
+    }
+}
+
+/// 解析字节流，返回完整的按键与消耗的字节数
+///
+/// 结尾不完整的转义序列或UTF-8字符会被保留，等待后续字节
+pub fn parse(bytes: &[u8]) -> (Vec<Key>, usize) {
+    let mut keys = Vec::new();
+    let mut idx = 0;
+    while idx < bytes.len() {
+        match parse_one(&bytes[idx..]) {
+            Some((key, len)) => {
+                keys.push(key);
+                idx += len;
+            }
+            None => break,
+        }
+    }
+    (keys, idx)
+}
+
+/// 解析单个按键，字节不足时返回`None`
+fn parse_one(bytes: &[u8]) -> Option<(Key, usize)> {
+    let key = match bytes[0] {
+        27 => return parse_esc(bytes),
+        b'\r' | b'\n' => Key::Char('\r'),
+        b'\t' => Key::Char('\t'),
+        0x7f => Key::Backspace,
+        c @ 0x01..=0x1a => Key::Ctrl((c - 0x1 + b'a') as char),
+        c @ 0x1c..=0x1f => Key::Ctrl((c - 0x1c + b'4') as char),
+        0 => Key::Ctrl(' '),
+        _ => return parse_utf8(bytes).map(|(ch, len)| (Key::Char(ch), len)),
+    };
+    Some((key, 1))
+}
+
+/// 解析以`ESC`开头的序列，结尾单独的`ESC`视为<Esc>键
+fn parse_esc(bytes: &[u8]) -> Option<(Key, usize)> {
+    match bytes.get(1) {
+        None => Some((Key::Esc, 1)),
+        Some(b'[') => parse_csi(bytes),
+        Some(b'O') => {
+            let key = match bytes.get(2)? {
+                val @ b'P'..=b'S' => Key::F(1 + val - b'P'),
+                b'A' => Key::Up,
+                b'B' => Key::Down,
+                b'C' => Key::Right,
+                b'D' => Key::Left,
+                b'H' => Key::Home,
+                b'F' => Key::End,
+                _ => Key::Other(bytes[..3].to_vec()),
+            };
+            Some((key, 3))
+        }
+        Some(_) => {
+            let (ch, len) = parse_utf8(&bytes[1..])?;
+            Some((Key::Alt(ch), len + 1))
+        }
+    }
+}
+
+/// 解析`ESC [`开头的控制序列
+fn parse_csi(bytes: &[u8]) -> Option<(Key, usize)> {
+    // Linux控制台的F1~F5：`ESC [ [ A`~`ESC [ [ E`
+    if bytes.get(2) == Some(&b'[') {
+        let key = match bytes.get(3)? {
+            val @ b'A'..=b'E' => Key::F(1 + val - b'A'),
+            _ => Key::Other(bytes[..4].to_vec()),
+        };
+        return Some((key, 4));
+    }
+
+    // 参数字节与中间字节之后是范围在`0x40`~`0x7e`的结束字节
+    let end = bytes[2..].iter().position(|c| (0x40..=0x7e).contains(c))? + 2;
+    let (params, code) = (&bytes[2..end], bytes[end]);
+    let len = end + 1;
+
+    let key = match (params, code) {
+        (b"", b'A') => Key::Up,
+        (b"", b'B') => Key::Down,
+        (b"", b'C') => Key::Right,
+        (b"", b'D') => Key::Left,
+        (b"", b'H') => Key::Home,
+        (b"", b'F') => Key::End,
+        (b"", b'Z') => Key::BackTab,
+        (_, b'~') => match std::str::from_utf8(params).ok()?.parse::<u8>() {
+            Ok(1 | 7) => Key::Home,
+            Ok(2) => Key::Insert,
+            Ok(3) => Key::Delete,
+            Ok(4 | 8) => Key::End,
+            Ok(5) => Key::PageUp,
+            Ok(6) => Key::PageDown,
+            Ok(v @ 11..=15) => Key::F(v - 10),
+            Ok(v @ 17..=21) => Key::F(v - 11),
+            Ok(v @ 23..=24) => Key::F(v - 12),
+            _ => Key::Other(bytes[..len].to_vec()),
+        },
+        _ => Key::Other(bytes[..len].to_vec()),
+    };
+    Some((key, len))
+}
+
+/// 解析一个UTF-8字符，无效字节会被替换为`U+FFFD`
+fn parse_utf8(bytes: &[u8]) -> Option<(char, usize)> {
+    let len = match bytes[0] {
+        0x00..=0x7f => 1,
+        0xc0..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf7 => 4,
+        _ => return Some((char::REPLACEMENT_CHARACTER, 1)),
+    };
+    let bytes = bytes.get(..len)?;
+    match std::str::from_utf8(bytes) {
+        Ok(st) => st.chars().next().map(|ch| (ch, len)),
+        Err(_) => Some((char::REPLACEMENT_CHARACTER, len)),
+    }
+}
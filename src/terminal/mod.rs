@@ -1,2 +1,4 @@
 pub mod cursor;
+pub mod key;
 pub mod term;
+pub mod tty;
@@ -1,4 +1,5 @@
 use std::arch::asm;
+use std::io::{self, Write};
 
 /// 存储目前的终端长宽
 pub struct Term {
@@ -28,6 +29,17 @@ impl Term {
         (self.height * self.width) as usize
     }
 
+    /// 开启鼠标按键与拖动事件的上报（SGR格式）
+    #[inline]
+    pub fn enable_mouse() {
+        print!("\x1b[?1002h\x1b[?1006h")
+    }
+
+    #[inline]
+    pub fn disable_mouse() {
+        print!("\x1b[?1006l\x1b[?1002l")
+    }
+
     /// 内联汇编实现系统调用，得到窗口尺寸
     pub fn init(&mut self) {
         let winsize: WinSize = Default::default();
@@ -49,3 +61,22 @@ impl Term {
         self.width = winsize.ws_row;
     }
 }
+
+/// 存在期间开启鼠标事件的上报，离开作用域时关闭
+///
+/// 出错提前返回或panic时同样会关闭，避免退出后终端继续上报鼠标事件
+pub struct MouseGuard;
+
+impl MouseGuard {
+    pub fn enable() -> Self {
+        Term::enable_mouse();
+        MouseGuard
+    }
+}
+
+impl Drop for MouseGuard {
+    fn drop(&mut self) {
+        Term::disable_mouse();
+        let _ = io::stdout().flush();
+    }
+}
@@ -0,0 +1,65 @@
+/// 标准输入输出的重定向
+///
+/// 从管道读取内容或把结果写入管道时，界面与键盘需要改用控制终端`/dev/tty`；
+/// 与`term.rs`相同，通过内联汇编直接进行系统调用
+use std::arch::asm;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::os::fd::{AsRawFd, FromRawFd};
+
+pub const STDIN: i32 = 0;
+pub const STDOUT: i32 = 1;
+
+/// 参考: https://www.man7.org/linux/man-pages/man2/dup.2.html
+fn dup(fd: i32) -> io::Result<i32> {
+    let res: i64;
+    unsafe {
+        asm!(
+            "syscall",
+            inlateout("rax") 32i64 => res, // __NR_dup
+            in("rdi") fd as i64,
+            lateout("rcx") _,
+            lateout("r11") _,
+        );
+    }
+    check(res)
+}
+
+/// 使`to`指向`fd`所指向的文件，`to`原来的文件会被关闭
+fn dup2(fd: i32, to: i32) -> io::Result<i32> {
+    let res: i64;
+    unsafe {
+        asm!(
+            "syscall",
+            inlateout("rax") 33i64 => res, // __NR_dup2
+            in("rdi") fd as i64,
+            in("rsi") to as i64,
+            lateout("rcx") _,
+            lateout("r11") _,
+        );
+    }
+    check(res)
+}
+
+#[inline]
+fn check(res: i64) -> io::Result<i32> {
+    if res < 0 {
+        Err(io::Error::from_raw_os_error(-res as i32))
+    } else {
+        Ok(res as i32)
+    }
+}
+
+/// 把`fd`重定向到控制终端
+pub fn to_tty(fd: i32) -> io::Result<()> {
+    let tty = OpenOptions::new().read(true).write(true).open("/dev/tty")?;
+    dup2(tty.as_raw_fd(), fd)?;
+    Ok(())
+}
+
+/// 复制`fd`，使重定向之后仍能写入原来的文件
+pub fn keep(fd: i32) -> io::Result<File> {
+    let new = dup(fd)?;
+    // `new`是新分配的描述符，由返回的`File`独占
+    Ok(unsafe { File::from_raw_fd(new) })
+}
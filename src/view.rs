@@ -7,11 +7,14 @@ use std::ops::Add;
 use widestring::Utf16String;
 
 pub mod bottombar;
+pub mod filedialog;
 pub mod filetree;
 pub mod help;
+pub mod lineedit;
 pub mod mainview;
 pub mod menu;
 pub mod msgbox;
+pub mod picker;
 pub mod topbar;
 
 pub type ViewID = u64;
@@ -1,6 +1,6 @@
 /// BottomBar为最底下的状态栏
 ///
-/// 显示目前视图，文件名及文件大小
+/// 显示目前视图，文件名，文件大小及插入/覆盖模式
 use crate::prelude::*;
 
 #[view("BottomBar")]
@@ -13,6 +13,8 @@ pub struct BottomBar {
     bcolor_lv2: Color,
     fcolor_lv2: Color,
 
+    /// 插入（INS）或覆盖（OVR）模式，由主视图通知
+    mode: String,
     content: String,
 }
 
@@ -25,8 +27,14 @@ impl View for BottomBar {
         self.fcolor_lv2 = settings.theme.stress_fclr.clone();
         self.bcolor = settings.theme.black.clone();
         self.fcolor = settings.theme.normal_fclr.clone();
+        if self.mode.is_empty() {
+            self.mode = String::from("INS");
+        }
     }
     fn update(&mut self, module: &mut Module) {
+        if let Some(mode) = module.recvmsg(&self.name) {
+            self.mode = mode;
+        }
         let file_mod = &mut module.file_mod;
         let (bclr_lv1, fclr_lv1) = (&self.bcolor_lv1, &self.fcolor_lv1);
         let (bclr_lv2, fclr_lv2) = (&self.bcolor_lv2, &self.fcolor_lv2);
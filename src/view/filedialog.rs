@@ -0,0 +1,412 @@
+/// FileDialog提供选择文件的弹窗，用于打开与另存为
+///
+/// 输入框中的路径相对于工作区的根目录，也可以是绝对路径或以`~`开头，
+/// 下方列出路径所在目录中以输入的文件名开头的文件，目录以`/`结尾
+/// - <Tab>补全文件名，<Up>/<Down>/<PageUp>/<PageDown>选择
+/// - <Enter>进入选中的目录或确认选中的文件，没有选中时确认输入的路径
+/// - <Esc>取消
+///
+/// 另存为时，文件已存在会询问是否覆盖，目录不存在会询问是否创建
+use super::lineedit::{Edited, LineEdit};
+use super::msgbox::{Answer, MsgBox};
+use crate::prelude::*;
+use getch_rs::Key;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// 弹窗的用途
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Mode {
+    #[default]
+    Open,
+    Save,
+}
+
+/// 目录中的一项
+#[derive(Debug, Clone)]
+struct Entry {
+    name: String,
+    is_dir: bool,
+}
+
+#[view("FileDialog")]
+#[start=(1, 1)]
+#[end=(2, 2)]
+pub struct FileDialog {
+    title: String,
+    mode: Mode,
+    input: LineEdit,
+    /// 相对路径的起点
+    base: PathBuf,
+    /// 列出的目录及其中的文件
+    listed: Option<PathBuf>,
+    entries: Vec<Entry>,
+    /// 以输入的文件名开头的文件
+    matches: Vec<usize>,
+    selected: Option<usize>,
+    scroll: usize,
+    /// 显示在下边框的提示或错误
+    status: String,
+    /// 确认的路径，取消时为`None`
+    chosen: Option<PathBuf>,
+    dir_clr: Color,
+    select_clr: Color,
+    weak_clr: Color,
+}
+
+impl View for FileDialog {
+    fn init(&mut self, module: &mut Module) {
+        let settings = &module.settings;
+        self.fcolor = settings.theme.normal_fclr.clone();
+        self.bcolor = settings.theme.normal_bclr.clone();
+        self.dir_clr = settings.theme.blue.clone();
+        self.select_clr = settings.theme.bright_black.clone();
+        self.weak_clr = settings.theme.weak_fclr.clone();
+    }
+    fn update(&mut self, module: &mut Module) {
+        let width = self.inner_width(&module.term);
+        self.input.set_width(width.saturating_sub(2));
+    }
+    fn matchar(&mut self, module: &mut Module, key: Key) {
+        let rows = self.rows(&module.term);
+        self.status.clear();
+        match key {
+            Key::Esc | Key::EOF => {
+                self.chosen = None;
+                self.lock = false;
+            }
+            Key::Char('\r') => match self.selected.map(|idx| self.matches[idx]) {
+                Some(idx) => {
+                    let entry = self.entries[idx].clone();
+                    self.fill(&entry);
+                    if !entry.is_dir {
+                        self.confirm(module);
+                    }
+                }
+                None => self.confirm(module),
+            },
+            Key::Char('\t') => self.complete(),
+            Key::Up => self.select(self.selected.map(|idx| idx.saturating_sub(1)), rows),
+            Key::Down => self.select(Some(self.selected.map_or(0, |idx| idx + 1)), rows),
+            Key::PageUp => self.select(self.selected.map(|idx| idx.saturating_sub(rows)), rows),
+            Key::PageDown => self.select(Some(self.selected.map_or(0, |idx| idx + rows)), rows),
+            key => {
+                if self.input.handle(&key) == Edited::Text {
+                    self.refresh_list();
+                }
+            }
+        }
+    }
+
+    fn set_cursor(&self, module: &mut Module) {
+        let (x, y) = self.get_start(&module.term);
+        let csr_x = x + 3 + self.input.cursor_col() as u16;
+        Cursor::set_csr(csr_x, y + 1);
+    }
+
+    fn draw(&self, module: &mut Module) -> io::Result<()> {
+        let term = &module.term;
+        self.refresh(term);
+        let (x, y) = self.get_start(term);
+        let width = self.inner_width(term);
+        let head = format!("{}{}", self.fcolor.fclr_head(), self.bcolor.bclr_head());
+
+        let title = format!(" {} ", self.title);
+        let dir = format!(" {} ", self.list_dir().display());
+        let dir: String = dir
+            .chars()
+            .skip(dir.chars().count().saturating_sub(width / 2))
+            .collect();
+        let fill = width.saturating_sub(title.chars().count() + dir.chars().count());
+        Cursor::set_csr(x, y);
+        println!("{head}╭{title}{}{dir}╮", "─".repeat(fill));
+
+        Cursor::csr_setcol(x);
+        println!("{head}│> {}│", self.input.render());
+
+        Cursor::csr_setcol(x);
+        println!("{head}├{}┤", "─".repeat(width));
+
+        for row in 0..self.rows(term) {
+            let line = match self.matches.get(self.scroll + row) {
+                Some(idx) => {
+                    let entry = &self.entries[*idx];
+                    let bclr = if self.selected == Some(self.scroll + row) {
+                        &self.select_clr
+                    } else {
+                        &self.bcolor
+                    };
+                    let (fclr, suffix) = if entry.is_dir {
+                        (&self.dir_clr, "/")
+                    } else {
+                        (&self.fcolor, "")
+                    };
+                    let name = format!("{}{suffix}", entry.name);
+                    let name: String = name.chars().take(width.saturating_sub(2)).collect();
+                    format!(
+                        "{}{} {name:<w$} ",
+                        bclr.bclr_head(),
+                        fclr.fclr_head(),
+                        w = width.saturating_sub(2)
+                    )
+                }
+                None => " ".repeat(width),
+            };
+            Cursor::csr_setcol(x);
+            println!("{head}│{line}{head}│");
+        }
+
+        let status = if self.status.is_empty() {
+            String::new()
+        } else {
+            format!(" {} ", self.status)
+        };
+        let status: String = status.chars().take(width).collect();
+        Cursor::csr_setcol(x);
+        print!(
+            "{head}╰{}{}{head}╯",
+            "─".repeat(width.saturating_sub(status.chars().count())),
+            status.fclr_head(&module.settings.theme.red)
+        );
+        io::stdout().flush()?;
+        Ok(())
+    }
+}
+
+#[allow(unused)]
+impl FileDialog {
+    pub fn pos(&mut self, start: (i16, i16), end: (i16, i16)) -> &mut Self {
+        let (x_s, y_s) = start;
+        let (x_e, y_e) = end;
+        self.start = (Pos::try_from(x_s).unwrap(), Pos::try_from(y_s).unwrap());
+        self.end = (Pos::try_from(x_e).unwrap(), Pos::try_from(y_e).unwrap());
+        self
+    }
+
+    /// 占据屏幕中部约三分之二的区域
+    pub fn default_pos(&mut self, module: &mut Module) -> &mut Self {
+        let (height, width) = (module.term.height as i16, module.term.width as i16);
+        let (margin_x, margin_y) = ((width / 6).max(1), (height / 6).max(1));
+        self.pos((margin_x, margin_y), (-margin_x, -margin_y))
+    }
+
+    pub fn title(&mut self, content: &str) -> &mut Self {
+        self.title = content.into();
+        self
+    }
+
+    pub fn mode(&mut self, mode: Mode) -> &mut Self {
+        self.mode = mode;
+        self
+    }
+
+    /// 相对路径的起点
+    pub fn base(&mut self, base: &Path) -> &mut Self {
+        self.base = base.to_path_buf();
+        self
+    }
+
+    /// 输入框的初始内容
+    pub fn input(&mut self, input: &str) -> &mut Self {
+        self.input.set_text(input);
+        self
+    }
+
+    /// 等待选择，返回确认的绝对路径，取消时返回`None`
+    pub fn wait(&mut self, module: &mut Module) -> Option<PathBuf> {
+        self.lock = true;
+        self.init(module);
+        self.update(module);
+        self.refresh_list();
+        self.draw(module).unwrap();
+        self.set_cursor(module);
+        io::stdout().flush().unwrap();
+
+        while self.lock {
+            let key = module.next_key();
+            self.matchar(module, key);
+
+            self.update(module);
+            self.draw(module).unwrap();
+            self.set_cursor(module);
+            io::stdout().flush().unwrap();
+        }
+
+        self.chosen.take()
+    }
+
+    /// 列表可以显示的行数
+    fn rows(&self, term: &Term) -> usize {
+        let (_, y) = self.get_start(term);
+        let (_, y_e) = self.get_end(term);
+        ((y_e - y) as usize).saturating_sub(4)
+    }
+
+    fn inner_width(&self, term: &Term) -> usize {
+        let (x, _) = self.get_start(term);
+        let (x_e, _) = self.get_end(term);
+        ((x_e - x) as usize).saturating_sub(2)
+    }
+
+    /// 把输入解析为绝对路径
+    fn resolve(&self, input: &str) -> PathBuf {
+        let home = std::env::var_os("HOME").map(PathBuf::from);
+        match (input.strip_prefix('~'), home) {
+            (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+                home.join(rest.trim_start_matches('/'))
+            }
+            _ => self.base.join(input),
+        }
+    }
+
+    /// 输入分为目录部分（含末尾的`/`）与文件名部分
+    fn split(&self) -> (String, String) {
+        let input = self.input.text();
+        match input.rfind('/') {
+            Some(idx) => (input[..=idx].to_string(), input[idx + 1..].to_string()),
+            None => (String::new(), input),
+        }
+    }
+
+    /// 输入的路径所在的目录
+    fn list_dir(&self) -> PathBuf {
+        let (dir, _) = self.split();
+        self.resolve(&dir)
+    }
+
+    /// 目录变化时重新读取，再按文件名过滤
+    fn refresh_list(&mut self) {
+        let dir = self.list_dir();
+        if self.listed.as_ref() != Some(&dir) {
+            self.entries = fs::read_dir(&dir)
+                .map(|entries| {
+                    entries
+                        .flatten()
+                        .map(|entry| Entry {
+                            name: entry.file_name().to_string_lossy().into_owned(),
+                            // 跟随符号链接判断是否为目录
+                            is_dir: entry.path().is_dir(),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            // 目录在前，再按名字排序
+            self.entries
+                .sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+            self.listed = Some(dir);
+        }
+
+        let (_, name) = self.split();
+        self.matches = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| {
+                entry.name.starts_with(&name)
+                    && (!entry.name.starts_with('.') || name.starts_with('.'))
+            })
+            .map(|(idx, _)| idx)
+            .collect();
+        self.selected = None;
+        self.scroll = 0;
+    }
+
+    fn select(&mut self, selected: Option<usize>, rows: usize) {
+        if self.matches.is_empty() {
+            return;
+        }
+        self.selected = selected.map(|idx| idx.min(self.matches.len() - 1));
+        let selected = self.selected.unwrap_or(0);
+        if selected < self.scroll {
+            self.scroll = selected;
+        } else if rows > 0 && selected >= self.scroll + rows {
+            self.scroll = selected + 1 - rows;
+        }
+    }
+
+    /// 用`entry`替换输入的文件名部分，目录会在末尾加上`/`
+    fn fill(&mut self, entry: &Entry) {
+        let (dir, _) = self.split();
+        let suffix = if entry.is_dir { "/" } else { "" };
+        self.input.set_text(&format!("{dir}{}{suffix}", entry.name));
+        self.refresh_list();
+    }
+
+    /// 补全文件名，只有一个候选项时直接补全，否则补全公共前缀
+    fn complete(&mut self) {
+        let names: Vec<&Entry> = self.matches.iter().map(|idx| &self.entries[*idx]).collect();
+        match names[..] {
+            [] => self.status = String::from("No Match"),
+            [entry] => {
+                let entry = entry.clone();
+                self.fill(&entry);
+            }
+            _ => {
+                let mut prefix: Vec<char> = names[0].name.chars().collect();
+                for entry in &names[1..] {
+                    let len = prefix
+                        .iter()
+                        .zip(entry.name.chars())
+                        .take_while(|(a, b)| *a == b)
+                        .count();
+                    prefix.truncate(len);
+                }
+                let (dir, _) = self.split();
+                let prefix: String = prefix.into_iter().collect();
+                self.input.set_text(&format!("{dir}{prefix}"));
+                self.refresh_list();
+            }
+        }
+    }
+
+    /// 确认输入的路径，目录会被打开，另存为时检查覆盖与缺少的目录
+    fn confirm(&mut self, module: &mut Module) {
+        let input = self.input.text();
+        if input.is_empty() {
+            return;
+        }
+        let path = self.resolve(&input);
+        if path.is_dir() {
+            if !input.ends_with('/') {
+                self.input.set_text(&format!("{input}/"));
+            }
+            self.refresh_list();
+            return;
+        }
+
+        let parent = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        if self.mode == Mode::Save {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            if path.exists() {
+                let answer = MsgBox::new()
+                    .title(&format!("Overwrite \"{name}\"?"))
+                    .default_answer(Answer::No)
+                    .default_pos(module)
+                    .confirm(module);
+                if answer != Answer::Yes {
+                    return;
+                }
+            } else if !parent.is_dir() {
+                let answer = MsgBox::new()
+                    .title(&format!("Create \"{}\"?", parent.display()))
+                    .default_pos(module)
+                    .confirm(module);
+                if answer != Answer::Yes {
+                    return;
+                }
+                if let Err(err) = fs::create_dir_all(&parent) {
+                    self.status = format!("Can't Create: {err}");
+                    return;
+                }
+            }
+        } else if !parent.is_dir() {
+            self.status = String::from("No Such Directory");
+            return;
+        }
+
+        self.chosen = Some(path);
+        self.lock = false;
+    }
+}
@@ -2,15 +2,51 @@
 ///
 /// 在被聚焦时可以通过方向键移动
 /// 输入<Enter>可以打开文件或者展开文件夹
+///
+/// 打开多个目录时作为工作区，每个根目录为一个顶层节点
+///
+/// 文件操作作用于选中的文件或目录，需要时弹窗确认：
+/// - <a>/<A>：在选中的目录（选中文件时为其所在目录）中新建文件/目录，名字中的`/`会创建多层目录
+/// - <r>：重命名
+/// - <m>/<c>：移动/复制到文件选择框中选择的路径
+/// - <d>：在同一目录中创建副本
+/// - <Delete>：移到回收站
+///
+/// 重命名或移动后，其中已打开的文件会使用新的路径
+///
+/// 默认隐藏以`.`开头的文件与被忽略规则匹配的文件，<H>/<I>切换是否显示，见`ignore`
+///
+/// 输入</>进入过滤模式，只显示名字中含有输入内容（不区分大小写，含有`/`时匹配相对路径）的文件与其上级目录：
+/// - <Up>/<Down>移动光标，<Enter>结束过滤并定位到选中的文件或目录，选中文件时打开
+/// - 输入为空时按<Backspace>取消过滤
 use crate::prelude::*;
+use crate::view::filedialog::{self, FileDialog};
+use crate::view::lineedit::{Edited, LineEdit};
+use crate::view::msgbox::Answer;
+use crate::MsgBox;
 
 use getch_rs::Key;
 use std::{
-    fs::{DirEntry, Metadata},
+    collections::HashMap,
+    fs::{self, DirEntry, File},
     io::{self, Write},
-    path::PathBuf,
+    mem,
+    path::{Path, PathBuf},
 };
 
+mod detail;
+mod ignore;
+mod ops;
+mod sort;
+mod trash;
+
+use detail::{Columns, Detail};
+use ignore::Ignore;
+use sort::Sort;
+
+/// 过滤模式最多读取的文件数，防止在很大的目录中卡住
+const FILTER_LIMIT: usize = 20000;
+
 macro_rules! impl_icon_color {
     (
         $(
@@ -74,16 +110,23 @@ struct Dir {
     dir_items: Vec<DirItem>,
     path: PathBuf,
     is_show: bool,
+    /// 所在目录中的文件使用的过滤条件
+    ignore: Ignore,
+    sort: Sort,
+    detail: Detail,
 }
 
 impl Dir {
-    fn new(name: String, path: PathBuf, color: (Color, Color)) -> Self {
+    fn new(name: String, path: PathBuf, color: (Color, Color), ignore: Ignore, sort: Sort) -> Self {
         Dir {
             name,
             color,
             dir_items: Vec::new(),
             path,
             is_show: false,
+            ignore,
+            sort,
+            detail: Detail::default(),
         }
     }
 
@@ -104,15 +147,12 @@ impl Dir {
             "{}{} {}",
             bclr.bclr_head(),
             fclr.fclr_head(),
-            self.path
-                .file_name()
-                .unwrap()
-                .to_str()
-                .unwrap()
-                .fcolor(fclr)
+            dir_name(&self.path).fcolor(fclr)
         );
         self.name = new_dir_name;
-        self.dir_items = read_dir_item(&self.path, bclr, fclr);
+        let ignore = self.ignore.child(&self.path);
+        let prev = mem::take(&mut self.dir_items);
+        self.dir_items = reload(prev, &self.path, bclr, fclr, &ignore, self.sort);
         self.is_show = true;
     }
 
@@ -122,12 +162,7 @@ impl Dir {
             "{}{} {}",
             bclr.bclr_head(),
             fclr.fclr_head(),
-            self.path
-                .file_name()
-                .unwrap()
-                .to_str()
-                .unwrap()
-                .fcolor(fclr)
+            dir_name(&self.path).fcolor(fclr)
         );
         self.name = new_dir_name;
         self.is_show = false;
@@ -145,7 +180,7 @@ impl Dir {
                         flat.append(&mut recu);
                     }
                 }
-                DirItem::File(name, _) => {
+                DirItem::File(name, ..) => {
                     flat.push(head + &name.clone());
                 }
             }
@@ -154,18 +189,73 @@ impl Dir {
     }
 }
 
-fn read_dir_item(path: &PathBuf, bclr: &Color, fclr: &Color) -> Vec<DirItem> {
+/// 目录名，没有名字（如`/`）时使用完整路径
+fn dir_name(path: &Path) -> String {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .map(String::from)
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+/// 重新读取目录，子目录沿用之前的展开状态
+fn reload(
+    prev: Vec<DirItem>,
+    path: &PathBuf,
+    bclr: &Color,
+    fclr: &Color,
+    ignore: &Ignore,
+    sort: Sort,
+) -> Vec<DirItem> {
+    let mut prev: HashMap<PathBuf, Dir> = prev
+        .into_iter()
+        .filter_map(|item| match item {
+            DirItem::Dir(dir) => Some((dir.path.clone(), dir)),
+            DirItem::File(..) => None,
+        })
+        .collect();
+    read_dir_item(path, bclr, fclr, ignore, sort)
+        .into_iter()
+        .map(|item| match item {
+            DirItem::Dir(mut dir) => {
+                if let Some(prev) = prev.remove(&dir.path) {
+                    dir.dir_items = prev.dir_items;
+                    if prev.is_show {
+                        dir.open();
+                    }
+                }
+                DirItem::Dir(dir)
+            }
+            item => item,
+        })
+        .collect()
+}
+
+/// 读取目录中没有被过滤的文件，无法读取时为空
+fn read_dir_item(
+    path: &PathBuf,
+    bclr: &Color,
+    fclr: &Color,
+    ignore: &Ignore,
+    sort: Sort,
+) -> Vec<DirItem> {
+    let entries = match fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
     let mut dir_items = Vec::new();
-    for entry in std::fs::read_dir(path).unwrap() {
-        let dir = entry.unwrap();
-        let dir_item = get_item(dir, bclr, fclr);
+    for dir in entries.flatten() {
+        let is_dir = dir.file_type().is_ok_and(|file_type| file_type.is_dir());
+        if ignore.is_ignored(&dir.path(), is_dir) {
+            continue;
+        }
+        let dir_item = get_item(dir, bclr, fclr, ignore, sort);
         dir_items.push(dir_item);
     }
-    dir_items.sort_unstable_by_key(|file| if let DirItem::Dir(_) = file { 0 } else { 1 });
+    sort.sort(&mut dir_items);
     dir_items
 }
 
-fn get_item(dir: DirEntry, bclr: &Color, fclr: &Color) -> DirItem {
+fn get_item(dir: DirEntry, bclr: &Color, fclr: &Color, ignore: &Ignore, sort: Sort) -> DirItem {
     let file_type = dir.file_type().unwrap();
     let icon = if file_type.is_dir() {
         "".to_string().fclr_head(fclr)
@@ -185,20 +275,40 @@ fn get_item(dir: DirEntry, bclr: &Color, fclr: &Color) -> DirItem {
     );
 
     let dir_path = dir.path().clone();
+    let detail = Detail::new(&dir);
     if file_type.is_dir() {
-        DirItem::Dir(Dir::new(dir_name, dir_path, (bclr.clone(), fclr.clone())))
+        let color = (bclr.clone(), fclr.clone());
+        let mut dir = Dir::new(dir_name, dir_path, color, ignore.clone(), sort);
+        dir.detail = detail;
+        DirItem::Dir(dir)
     } else {
-        DirItem::File(dir_name, dir_path)
+        DirItem::File(dir_name, dir_path, detail)
     }
 }
 
 #[derive(Debug, Clone)]
 enum DirItem {
     Dir(Dir),
-    File(String, PathBuf),
+    File(String, PathBuf, Detail),
 }
 
 impl DirItem {
+    #[inline]
+    fn path(&self) -> &PathBuf {
+        match self {
+            DirItem::Dir(dir) => &dir.path,
+            DirItem::File(_, path, _) => path,
+        }
+    }
+
+    #[inline]
+    fn detail(&self) -> &Detail {
+        match self {
+            DirItem::Dir(dir) => &dir.detail,
+            DirItem::File(_, _, detail) => detail,
+        }
+    }
+
     fn len(&self) -> usize {
         if let DirItem::Dir(dir) = self {
             dir.len()
@@ -222,7 +332,7 @@ impl DirItem {
                     false
                 }
             }
-            DirItem::File(_, pathbuf) => path == pathbuf,
+            DirItem::File(_, pathbuf, _) => path == pathbuf,
         }
     }
     */
@@ -246,17 +356,43 @@ impl DirItem {
     }
 }
 
+/// 影响读取的`(hidden, ignored, exclude, 排序方式)`设置
+type Options = (bool, bool, Vec<String>, Sort);
+
+/// 过滤模式的状态
+#[derive(Debug, Clone, Default)]
+struct Filter {
+    input: LineEdit,
+    /// 读取了所有子目录的文件树
+    all: Vec<DirItem>,
+    /// 过滤后的文件树
+    items: Vec<DirItem>,
+    /// 进入过滤模式前的`(curr_line, scroll)`，取消时恢复
+    prev: (usize, usize),
+}
+
 #[view("FileTree")]
 #[start=(1, 2)]
 #[end=(26, -2)]
 pub struct FileTree {
     dir: String,
-    path: PathBuf,
+    /// 根目录，多于一个时为工作区
+    roots: Vec<PathBuf>,
     dir_items: Vec<DirItem>,
     flat: Vec<String>,
-    metadata: Option<Metadata>,
+    /// 与`flat`对应的详细信息
+    details: Vec<Detail>,
+    detail_clr: Color,
     curr_line: usize,
     scroll: usize,
+    /// 只有一个根目录时，其中的文件使用的过滤条件与排序方式
+    ignore: Ignore,
+    sort: Sort,
+    /// 读取时的设置，改变后重新读取
+    options: Options,
+    filter: Option<Filter>,
+    /// 自动定位时上次定位到的文件
+    followed: PathBuf,
 }
 
 impl View for FileTree {
@@ -267,42 +403,70 @@ impl View for FileTree {
             &settings.theme.normal_bclr.darken(0x4),
             &settings.theme.stress_fclr,
         );
-        let curr_dir = file_mod.curr_dir();
-        self.path = curr_dir.to_path_buf();
         self.bcolor = bclr.clone();
         self.fcolor = fclr.clone();
-        self.metadata = Some(self.path.metadata().unwrap());
-        let curr_dir = curr_dir.to_str().unwrap().to_string();
-        self.dir = curr_dir;
-        self.dir_items = read_dir_item(&self.path, bclr, fclr);
+        self.detail_clr = settings.theme.weak_fclr.clone();
+        // 根目录不变时（如切换主题）只更新颜色，保留展开状态、过滤与光标位置
+        if self.roots == file_mod.roots() {
+            self.recolor();
+            return;
+        }
+        self.roots = file_mod.roots().to_vec();
+        self.options = options(settings);
+        self.sort = Sort::new(settings);
+        if let [root] = &self.roots[..] {
+            self.dir = root.to_str().unwrap().to_string();
+            self.ignore = Ignore::new(root, settings).child(root);
+            self.dir_items = read_dir_item(root, bclr, fclr, &self.ignore, self.sort);
+        } else {
+            self.dir = format!("Workspace ({} roots)", self.roots.len());
+            self.dir_items = self
+                .roots
+                .iter()
+                .map(|root| {
+                    let color = (bclr.clone(), fclr.clone());
+                    let ignore = Ignore::new(root, settings);
+                    let mut dir = Dir::new(String::new(), root.clone(), color, ignore, self.sort);
+                    dir.detail = Detail::from_path(root);
+                    dir.close();
+                    DirItem::Dir(dir)
+                })
+                .collect();
+        }
+        self.filter = None;
+        self.followed = PathBuf::new();
+        self.curr_line = 0;
+        self.scroll = 0;
     }
 
-    fn update(&mut self, _: &mut Module) {
-        let metadata = self.path.metadata().unwrap();
-        let prev_metadata = self.metadata.as_ref().unwrap();
-        let bclr = &self.bcolor;
+    fn update(&mut self, module: &mut Module) {
+        if options(&module.settings) != self.options {
+            // 排序改变后光标仍停在原来的文件上
+            let curr = flatten(self.items()).get(self.curr_line).cloned();
+            self.rebuild(&module.settings);
+            if let Some(curr) = curr {
+                self.select(&curr, &module.term);
+            }
+        }
 
-        if metadata.modified().unwrap() != prev_metadata.modified().unwrap() {
-            let (bclr, fclr) = (&self.bcolor, &self.fcolor);
-            /*
-            for entry in std::fs::read_dir(&self.path).unwrap() {
-                let dir = entry.unwrap();
-                let path = dir.path();
-                for item in &self.dir_items {
-                    if !item.is_exist(&path) {
-                        let new = get_item(dir, bclr, fclr);
-                        self.dir_items.push(new);
-                        break;
-                    }
-                }
+        // 切换文件后定位到当前文件，过滤时不打断输入
+        if module.settings.is_follow && self.filter.is_none() {
+            let curr = module.file_mod.curr().pathbuf().clone();
+            if curr != self.followed {
+                self.select(&curr, &module.term);
+                self.followed = curr;
             }
-            */
-            self.dir_items = read_dir_item(&self.path, bclr, fclr);
-            self.curr_line = 0;
-            self.metadata = Some(metadata);
-        };
+        }
+
+        let changed = module.watcher.take();
+        if !changed.is_empty() {
+            self.patch(changed, &module.settings);
+        }
+        module.watcher.sync(&self.shown_dirs());
 
-        let flat = self.dir_items.iter().fold(Vec::new(), |init, item| {
+        let bclr = &self.bcolor;
+
+        let flat = self.items().iter().fold(Vec::new(), |init, item| {
             let mut flat = init;
             let head = format!("{}   ", bclr.bclr_head());
             match &item {
@@ -313,21 +477,37 @@ impl View for FileTree {
                         flat.append(&mut recu);
                     }
                 }
-                DirItem::File(name, _) => {
+                DirItem::File(name, ..) => {
                     flat.push(head + &name.clone());
                 }
             }
             flat
         });
         self.flat = flat;
+        self.details = flatten_details(self.items());
     }
     fn matchar(&mut self, module: &mut Module, key: getch_rs::Key) {
+        if self.filter.is_some() {
+            self.filter_key(module, key);
+            return;
+        }
         let term = &module.term;
         match key {
             Key::Char('\r') => {
                 self.enter(module);
             }
-            Key::Delete => {}
+            Key::Char('a') => self.create(module, false),
+            Key::Char('A') => self.create(module, true),
+            Key::Char('r') => self.rename(module),
+            Key::Char('m') => self.transfer(module, true),
+            Key::Char('c') => self.transfer(module, false),
+            Key::Char('d') => self.duplicate(module),
+            Key::Delete => self.delete(module),
+            Key::Char('/') => self.start_filter(),
+            Key::Char('H') => self.toggle(module, "hidden"),
+            Key::Char('I') => self.toggle(module, "ignored"),
+            Key::Char('s') => self.cycle_sort(module),
+            Key::Char('f') => self.reveal(module),
             Key::Up => {
                 self.up();
             }
@@ -358,19 +538,31 @@ impl View for FileTree {
 
         Cursor::set_csr(x, y);
 
-        let mut dir = self.dir.clone();
-        dir.truncate(max as usize);
+        let dir = match &self.filter {
+            Some(filter) => format!("/{}", filter.input.text()),
+            None => self.dir.clone(),
+        };
+        let dir: String = dir.chars().take(max as usize).collect();
 
         println!("{}", dir.color(bclr, fclr));
 
-        let mut height_cnt = 1;
-        for line in self.flat.iter().skip(self.scroll) {
-            print!("{}", line);
-            height_cnt += 1;
-            if height_cnt > max_height {
+        // 足够宽时在右侧显示详细信息，覆盖过长的名字
+        let columns = if module.settings.is_show_details {
+            Columns::fit(max as usize)
+        } else {
+            Columns::default()
+        };
+        let details = self.details.iter().skip(self.scroll);
+        for (row, (line, detail)) in self.flat.iter().skip(self.scroll).zip(details).enumerate() {
+            if row as u16 >= max_height {
                 break;
             }
-            Cursor::csr_nextline();
+            Cursor::set_csr(x, y + 1 + row as u16);
+            print!("{}", line);
+            if !columns.is_empty() {
+                Cursor::set_csr(x_e - columns.width() as u16, y + 1 + row as u16);
+                print!("{}", columns.render(detail).color(bclr, &self.detail_clr));
+            }
         }
 
         io::stdout().flush()?;
@@ -380,15 +572,26 @@ impl View for FileTree {
 
 impl FileTree {
     pub fn len(&self) -> usize {
-        self.dir_items.iter().fold(0, |sum, item| sum + item.len())
+        self.items().iter().fold(0, |sum, item| sum + item.len())
+    }
+
+    /// 显示的文件树，过滤模式下为过滤后的
+    #[inline]
+    fn items(&self) -> &[DirItem] {
+        match &self.filter {
+            Some(filter) => &filter.items,
+            None => &self.dir_items,
+        }
     }
 
     #[inline]
     pub fn enter(&mut self, module: &mut Module) {
-        let file_mod = &mut module.file_mod;
         let curr_line = self.curr_line;
         let flat = flatten(&self.dir_items);
-        let path = flat.get(curr_line).unwrap();
+        let path = match flat.get(curr_line) {
+            Some(path) => path,
+            None => return,
+        };
         if path.is_dir() {
             for item in self.dir_items.iter_mut() {
                 let res = item.search_mut_dir(path);
@@ -402,10 +605,460 @@ impl FileTree {
                 }
             }
         } else {
-            let latest = file_mod.insert_from_path(path);
-            module.sendmsg(String::from("MainView"), latest.to_string());
-            module.push_op(Op::Shift(String::from("MainView")));
+            open_file(module, path);
+        }
+    }
+
+    /// 切换设置中的`option`，之后在`update`中重新读取
+    fn toggle(&mut self, module: &mut Module, option: &str) {
+        let settings = &mut module.settings;
+        let value = settings.get(option) == Some(String::from("on"));
+        let value = if value { "off" } else { "on" };
+        settings.set(option, value).unwrap();
+        module.sendmsg(String::from("Menu"), format!("{option}={value}"));
+    }
+
+    /// 定位到当前文件
+    fn reveal(&mut self, module: &mut Module) {
+        let curr = module.file_mod.curr().pathbuf().clone();
+        if curr.as_os_str().is_empty() {
+            return error(module, "No File Name");
+        }
+        if !self.select(&curr, &module.term) {
+            error(module, "Not in the Tree");
+        }
+    }
+
+    /// 切换到下一种排序方式，之后在`update`中重新排序
+    fn cycle_sort(&mut self, module: &mut Module) {
+        let settings = &mut module.settings;
+        settings.sort_by = settings.sort_by.next();
+        let msg = format!("sort={}", settings.sort_by.name());
+        module.sendmsg(String::from("Menu"), msg);
+    }
+
+    /// 过滤条件改变后重新读取整个文件树，保持展开状态
+    fn rebuild(&mut self, settings: &Settings) {
+        self.options = options(settings);
+        self.sort = Sort::new(settings);
+        self.filter = None;
+        let (bclr, fclr) = (&self.bcolor, &self.fcolor);
+        if let [root] = &self.roots[..] {
+            self.ignore = Ignore::new(root, settings).child(root);
+            let prev = mem::take(&mut self.dir_items);
+            self.dir_items = reload(prev, root, bclr, fclr, &self.ignore, self.sort);
+        } else {
+            for (root, item) in self.roots.iter().zip(self.dir_items.iter_mut()) {
+                if let DirItem::Dir(dir) = item {
+                    dir.ignore = Ignore::new(root, settings);
+                    dir.sort = self.sort;
+                    if dir.is_show {
+                        dir.open();
+                    }
+                }
+            }
+        }
+        self.curr_line = self.curr_line.min(self.len().saturating_sub(1));
+        self.scroll = self.scroll.min(self.curr_line);
+    }
+
+    /// 以新的颜色重新生成文件树，保持展开状态
+    fn recolor(&mut self) {
+        let (bclr, fclr) = (&self.bcolor, &self.fcolor);
+        if let [root] = &self.roots[..] {
+            let prev = mem::take(&mut self.dir_items);
+            self.dir_items = reload(prev, root, bclr, fclr, &self.ignore, self.sort);
+        } else {
+            for item in self.dir_items.iter_mut() {
+                if let DirItem::Dir(dir) = item {
+                    dir.color = (bclr.clone(), fclr.clone());
+                    if dir.is_show {
+                        dir.open();
+                    } else {
+                        dir.close();
+                    }
+                }
+            }
+        }
+        if let Some(filter) = &mut self.filter {
+            let mut all = self.dir_items.clone();
+            let mut budget = FILTER_LIMIT;
+            load_all(&mut all, &mut budget);
+            let pattern = filter.input.text().to_lowercase();
+            let roots = &self.roots;
+            filter.items = if pattern.is_empty() {
+                self.dir_items.clone()
+            } else {
+                filter_items(&all, &|path: &Path| is_match(path, &pattern, roots))
+            };
+            filter.all = all;
+        }
+        self.curr_line = self.curr_line.min(self.len().saturating_sub(1));
+        self.scroll = self.scroll.min(self.curr_line);
+    }
+
+    /// 进入过滤模式，读取所有子目录
+    fn start_filter(&mut self) {
+        let mut all = self.dir_items.clone();
+        let mut budget = FILTER_LIMIT;
+        load_all(&mut all, &mut budget);
+        self.filter = Some(Filter {
+            input: LineEdit::default(),
+            all,
+            items: self.dir_items.clone(),
+            prev: (self.curr_line, self.scroll),
+        });
+    }
+
+    fn filter_key(&mut self, module: &mut Module, key: Key) {
+        let term = &module.term;
+        match key {
+            Key::Char('\r') => self.end_filter(module, true),
+            Key::Backspace if self.filter.as_ref().is_some_and(|f| f.input.is_empty()) => {
+                self.end_filter(module, false)
+            }
+            Key::Up => self.up(),
+            Key::Down => self.down(term),
+            key => {
+                let edited = match &mut self.filter {
+                    Some(filter) => filter.input.handle(&key),
+                    None => Edited::Nothing,
+                };
+                if edited == Edited::Text {
+                    self.apply_filter(term);
+                }
+            }
+        }
+    }
+
+    /// 按输入过滤文件树，光标移到第一个匹配的文件或目录
+    fn apply_filter(&mut self, term: &Term) {
+        let filter = match &mut self.filter {
+            Some(filter) => filter,
+            None => return,
+        };
+        let pattern = filter.input.text().to_lowercase();
+        let roots = &self.roots;
+        let is_match = |path: &Path| is_match(path, &pattern, roots);
+        filter.items = if pattern.is_empty() {
+            self.dir_items.clone()
+        } else {
+            filter_items(&filter.all, &is_match)
+        };
+        let first = flatten(&filter.items)
+            .iter()
+            .position(|path| !pattern.is_empty() && is_match(path));
+        let height = (self.end.1.unwrap(term.height) - self.start.1.unwrap(term.height)) as usize;
+        self.curr_line = first.unwrap_or(0);
+        self.scroll = (self.curr_line + 4).saturating_sub(height);
+    }
+
+    /// 结束过滤，`is_accept`为真时定位到选中的文件或目录，选中文件时打开
+    fn end_filter(&mut self, module: &mut Module, is_accept: bool) {
+        let filter = match self.filter.take() {
+            Some(filter) => filter,
+            None => return,
+        };
+        let path = flatten(&filter.items).get(self.curr_line).cloned();
+        match path {
+            Some(path) if is_accept => {
+                self.select(&path, &module.term);
+                if !path.is_dir() {
+                    open_file(module, &path);
+                }
+            }
+            _ => (self.curr_line, self.scroll) = filter.prev,
+        }
+    }
+
+    /// 在选中的目录中新建文件或目录
+    fn create(&mut self, module: &mut Module, is_dir: bool) {
+        let dir = self.target_dir();
+        let title = if is_dir { "New Directory" } else { "New File" };
+        let name = match MsgBox::new()
+            .title(title)
+            .default_pos(module)
+            .ask::<String>(module)
+        {
+            Some(Ok(name)) if !name.trim().is_empty() => name.trim().to_string(),
+            _ => return,
+        };
+        let path = dir.join(&name);
+        if path.symlink_metadata().is_ok() {
+            error(module, &format!("\"{name}\" Already Exists"));
+            return;
+        }
+        let ret = if is_dir {
+            fs::create_dir_all(&path)
+        } else {
+            path.parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| File::create_new(&path).map(|_| ()))
+        };
+        match ret {
+            Ok(()) => {
+                self.reload_dir(&dir);
+                self.select(&path, &module.term);
+                module.sendmsg(String::from("Menu"), format!("Created \"{name}\""));
+            }
+            Err(err) => error(module, &format!("Can't Create: {err}")),
+        }
+    }
+
+    /// 重命名选中的文件或目录
+    fn rename(&mut self, module: &mut Module) {
+        let path = match self.selected(module) {
+            Some(path) => path,
+            None => return,
+        };
+        let name = file_name(&path);
+        let new_name = match MsgBox::new()
+            .title("Rename")
+            .input(&name)
+            .default_pos(module)
+            .ask::<String>(module)
+        {
+            Some(Ok(new_name)) if !new_name.trim().is_empty() => new_name.trim().to_string(),
+            _ => return,
+        };
+        if new_name == name {
+            return;
+        }
+        if new_name.contains('/') {
+            error(module, &format!("Invalid Name: \"{new_name}\""));
+            return;
+        }
+        let to = path.with_file_name(&new_name);
+        if to.symlink_metadata().is_ok() {
+            error(module, &format!("\"{new_name}\" Already Exists"));
+            return;
+        }
+        match fs::rename(&path, &to) {
+            Ok(()) => {
+                module.file_mod.moved(&path, &to);
+                self.reload_dir(to.parent().unwrap());
+                self.select(&to, &module.term);
+                module.sendmsg(String::from("Menu"), format!("Renamed to \"{new_name}\""));
+            }
+            Err(err) => error(module, &format!("Can't Rename: {err}")),
+        }
+    }
+
+    /// 把选中的文件或目录移动（`is_move`为真时）或复制到文件选择框中选择的路径
+    fn transfer(&mut self, module: &mut Module, is_move: bool) {
+        let path = match self.selected(module) {
+            Some(path) => path,
+            None => return,
+        };
+        let parent = path.parent().unwrap().to_path_buf();
+        let (title, verb) = if is_move {
+            ("Move To", "Move")
+        } else {
+            ("Copy To", "Copy")
+        };
+        let to = match FileDialog::new()
+            .title(title)
+            .mode(filedialog::Mode::Save)
+            .base(&parent)
+            .input(&file_name(&path))
+            .default_pos(module)
+            .wait(module)
+        {
+            Some(to) if to != path => to,
+            _ => return,
+        };
+        if to.starts_with(&path) {
+            error(module, &format!("Can't {verb} a Directory Into Itself"));
+            return;
+        }
+        let overwrite = to.symlink_metadata().is_ok();
+        let ret = if is_move {
+            ops::move_all(&path, &to)
+        } else {
+            ops::copy_all(&path, &to)
+        };
+        match ret {
+            Ok(()) => {
+                // 被覆盖的文件如果已打开，不再关联磁盘上的文件
+                if overwrite {
+                    module.file_mod.removed(&to);
+                }
+                let to = fs::canonicalize(&to).unwrap_or(to);
+                if is_move {
+                    module.file_mod.moved(&path, &to);
+                    self.reload_dir(&parent);
+                }
+                if let Some(dir) = to.parent() {
+                    self.reload_dir(dir);
+                }
+                self.select(&to, &module.term);
+                let msg = if is_move { "Moved" } else { "Copied" };
+                let msg = format!("{msg} to \"{}\"", to.display());
+                module.sendmsg(String::from("Menu"), msg);
+            }
+            Err(err) => error(module, &format!("Can't {verb}: {err}")),
+        }
+    }
+
+    /// 在同一目录中创建选中的文件或目录的副本
+    fn duplicate(&mut self, module: &mut Module) {
+        let path = match self.selected(module) {
+            Some(path) => path,
+            None => return,
+        };
+        let to = ops::copy_path(&path);
+        match ops::copy_all(&path, &to) {
+            Ok(()) => {
+                self.reload_dir(to.parent().unwrap());
+                self.select(&to, &module.term);
+                let msg = format!("Duplicated to \"{}\"", file_name(&to));
+                module.sendmsg(String::from("Menu"), msg);
+            }
+            Err(err) => error(module, &format!("Can't Duplicate: {err}")),
+        }
+    }
+
+    /// 确认后把选中的文件或目录移到回收站
+    fn delete(&mut self, module: &mut Module) {
+        let path = match self.selected(module) {
+            Some(path) => path,
+            None => return,
+        };
+        let name = file_name(&path);
+        let answer = MsgBox::new()
+            .title(&format!("Move \"{name}\" to Trash?"))
+            .default_answer(Answer::No)
+            .default_pos(module)
+            .confirm(module);
+        if answer != Answer::Yes {
+            return;
+        }
+        match trash::trash(&path) {
+            Ok(_) => {
+                module.file_mod.removed(&path);
+                self.reload_dir(path.parent().unwrap());
+                self.curr_line = self.curr_line.min(self.len().saturating_sub(1));
+                self.scroll = self.scroll.min(self.curr_line);
+                module.sendmsg(String::from("Menu"), format!("\"{name}\" Moved to Trash"));
+            }
+            Err(err) => error(module, &format!("Can't Delete: {err}")),
+        }
+    }
+
+    /// 选中的文件或目录，工作区的根目录不能被修改
+    fn selected(&self, module: &mut Module) -> Option<PathBuf> {
+        let path = flatten(&self.dir_items).get(self.curr_line).cloned()?;
+        if self.roots.contains(&path) {
+            error(module, "Can't Change a Workspace Root");
+            return None;
+        }
+        Some(path)
+    }
+
+    /// 新建文件的目录：选中的目录，选中文件时为其所在目录
+    fn target_dir(&self) -> PathBuf {
+        match flatten(&self.dir_items).get(self.curr_line) {
+            Some(path) if path.is_dir() => path.clone(),
+            Some(path) => path.parent().unwrap().to_path_buf(),
+            None => self.roots[0].clone(),
+        }
+    }
+
+    /// 重新读取目录`dir`，保持其中的展开状态
+    fn reload_dir(&mut self, dir: &Path) {
+        let (bclr, fclr) = (&self.bcolor, &self.fcolor);
+        if let [root] = &self.roots[..] {
+            if root == dir {
+                let prev = mem::take(&mut self.dir_items);
+                self.dir_items = reload(prev, root, bclr, fclr, &self.ignore, self.sort);
+                return;
+            }
+        }
+        let dir = dir.to_path_buf();
+        for item in self.dir_items.iter_mut() {
+            if let Some(dir) = item.search_mut_dir(&dir) {
+                if dir.is_show {
+                    dir.open();
+                }
+                break;
+            }
+        }
+    }
+
+    /// 文件系统发生变化后只重新读取受影响的目录，保持展开状态与选中的项
+    fn patch(&mut self, changed: Vec<PathBuf>, settings: &Settings) {
+        let curr = match self.filter {
+            Some(_) => None,
+            None => flatten(&self.dir_items).get(self.curr_line).cloned(),
+        };
+        // 事件队列溢出（空路径）或忽略规则改变时重新读取整个文件树
+        if changed
+            .iter()
+            .any(|path| path.as_os_str().is_empty() || ignore::is_rule_file(path))
+        {
+            self.rebuild(settings);
+        } else {
+            let mut dirs: Vec<&Path> = changed.iter().filter_map(|path| path.parent()).collect();
+            dirs.sort_unstable();
+            dirs.dedup();
+            for dir in dirs {
+                self.reload_dir(dir);
+            }
         }
+
+        if let Some(line) =
+            curr.and_then(|curr| flatten(self.items()).iter().position(|item| *item == curr))
+        {
+            self.curr_line = line;
+        }
+        self.curr_line = self.curr_line.min(self.len().saturating_sub(1));
+        self.scroll = self.scroll.min(self.curr_line);
+    }
+
+    /// 需要监听的目录：已读取内容的根目录与展开的目录
+    fn shown_dirs(&self) -> Vec<PathBuf> {
+        let mut dirs = Vec::new();
+        if let [root] = &self.roots[..] {
+            dirs.push(root.clone());
+        }
+        shown_dirs(&self.dir_items, &mut dirs);
+        dirs
+    }
+
+    /// 展开`path`的所有上级目录
+    fn expand_to(&mut self, path: &Path) {
+        let mut ancestors: Vec<PathBuf> = path.ancestors().skip(1).map(Path::to_path_buf).collect();
+        ancestors.reverse();
+        for ancestor in ancestors {
+            for item in self.dir_items.iter_mut() {
+                if let Some(dir) = item.search_mut_dir(&ancestor) {
+                    if !dir.is_show {
+                        dir.open();
+                    }
+                    break;
+                }
+            }
+        }
+    }
+
+    /// 展开上级目录，把光标移到`path`处并使其可见，`path`不在文件树中时返回`false`
+    fn select(&mut self, path: &Path, term: &Term) -> bool {
+        self.expand_to(path);
+        let line = match flatten(&self.dir_items)
+            .iter()
+            .position(|item| item == path)
+        {
+            Some(line) => line,
+            None => return false,
+        };
+        let height = (self.end.1.unwrap(term.height) - self.start.1.unwrap(term.height)) as usize;
+        self.curr_line = line;
+        if line < self.scroll {
+            self.scroll = line;
+        } else if line + 4 > self.scroll + height {
+            self.scroll = line + 4 - height;
+        }
+        true
     }
 
     #[inline]
@@ -421,7 +1074,7 @@ impl FileTree {
     pub fn down(&mut self, term: &Term) {
         let line = self.curr_line;
 
-        if line < self.len() - 1 {
+        if line + 1 < self.len() {
             self.line_inc(term);
         }
     }
@@ -451,6 +1104,100 @@ impl FileTree {
     }
 }
 
+/// 读取时使用的设置
+#[inline]
+fn options(settings: &Settings) -> Options {
+    (
+        settings.is_show_hidden,
+        settings.is_show_ignored,
+        settings.exclude.clone(),
+        Sort::new(settings),
+    )
+}
+
+/// 在主视图中打开文件
+fn open_file(module: &mut Module, path: &Path) {
+    let latest = match module.file_mod.open(path) {
+        Ok(id) => id,
+        Err(err) => return error(module, &format!("Can't Open: {err}")),
+    };
+    module.sendmsg(String::from("MainView"), latest.to_string());
+    module.push_op(Op::Shift(String::from("MainView")));
+}
+
+/// 展开所有子目录，读取的文件数超过`budget`后不再展开
+fn load_all(items: &mut [DirItem], budget: &mut usize) {
+    for item in items {
+        if let DirItem::Dir(dir) = item {
+            if *budget == 0 {
+                return;
+            }
+            dir.open();
+            *budget = budget.saturating_sub(dir.dir_items.len());
+            load_all(&mut dir.dir_items, budget);
+        }
+    }
+}
+
+/// 文件名（`pattern`含有`/`时为相对于根目录的路径）是否含有`pattern`，`pattern`为小写
+fn is_match(path: &Path, pattern: &str, roots: &[PathBuf]) -> bool {
+    let text = if pattern.contains('/') {
+        roots
+            .iter()
+            .find_map(|root| path.strip_prefix(root).ok())
+            .unwrap_or(path)
+            .to_string_lossy()
+    } else {
+        path.file_name().unwrap_or_default().to_string_lossy()
+    };
+    text.to_lowercase().contains(pattern)
+}
+
+/// 只保留匹配的文件与含有匹配的文件的目录，目录都展开
+fn filter_items(items: &[DirItem], is_match: &dyn Fn(&Path) -> bool) -> Vec<DirItem> {
+    items
+        .iter()
+        .filter_map(|item| match item {
+            DirItem::Dir(dir) => {
+                let dir_items = filter_items(&dir.dir_items, is_match);
+                if dir_items.is_empty() && !is_match(&dir.path) {
+                    return None;
+                }
+                let mut dir = Dir {
+                    name: dir.name.clone(),
+                    color: dir.color.clone(),
+                    dir_items,
+                    path: dir.path.clone(),
+                    is_show: true,
+                    ignore: dir.ignore.clone(),
+                    sort: dir.sort,
+                    detail: dir.detail,
+                };
+                if dir.dir_items.is_empty() {
+                    dir.close();
+                }
+                Some(DirItem::Dir(dir))
+            }
+            DirItem::File(_, path, _) => is_match(path).then(|| item.clone()),
+        })
+        .collect()
+}
+
+/// 弹窗显示错误
+fn error(module: &mut Module, msg: &str) {
+    MsgBox::new()
+        .title("Error")
+        .default_pos(module)
+        .show(module, msg);
+}
+
+#[inline]
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
 #[inline]
 fn flatten(dir_items: &[DirItem]) -> Vec<PathBuf> {
     let mut flat = Vec::new();
@@ -462,7 +1209,32 @@ fn flatten(dir_items: &[DirItem]) -> Vec<PathBuf> {
                 flat.append(&mut recu);
             }
         }
-        DirItem::File(_, path) => flat.push(path.clone()),
+        DirItem::File(_, path, _) => flat.push(path.clone()),
+    });
+    flat
+}
+
+fn shown_dirs(dir_items: &[DirItem], dirs: &mut Vec<PathBuf>) {
+    for item in dir_items {
+        if let DirItem::Dir(dir) = item {
+            if dir.is_show {
+                dirs.push(dir.path.clone());
+                shown_dirs(&dir.dir_items, dirs);
+            }
+        }
+    }
+}
+
+/// 与`flatten`顺序相同的详细信息
+fn flatten_details(dir_items: &[DirItem]) -> Vec<Detail> {
+    let mut flat = Vec::new();
+    dir_items.iter().for_each(|item| {
+        flat.push(*item.detail());
+        if let DirItem::Dir(dir) = item {
+            if dir.is_show {
+                flat.append(&mut flatten_details(&dir.dir_items));
+            }
+        }
     });
     flat
 }
@@ -0,0 +1,199 @@
+/// 文件树的详细信息：权限、大小与修改时间
+///
+/// 文件树变宽时按大小、修改时间、权限的顺序加入能放下的列，名字至少保留`NAME_WIDTH`列；
+/// 没有时区数据库可用，时间按UTC显示
+use std::{
+    fs::{self, DirEntry, Metadata},
+    os::unix::fs::MetadataExt,
+    path::Path,
+};
+
+/// 名字至少占的列数
+const NAME_WIDTH: usize = 24;
+const PERM_WIDTH: usize = 10;
+const SIZE_WIDTH: usize = 5;
+/// `YYYY-MM-DD hh:mm`
+const TIME_WIDTH: usize = 16;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Detail {
+    pub size: u64,
+    /// 修改时间，1970-01-01起的秒数
+    pub modified: i64,
+    mode: u32,
+    is_dir: bool,
+    is_symlink: bool,
+}
+
+impl Detail {
+    /// 符号链接显示其指向的文件的大小与时间
+    pub fn new(entry: &DirEntry) -> Self {
+        let is_symlink = entry
+            .file_type()
+            .is_ok_and(|file_type| file_type.is_symlink());
+        let metadata = fs::metadata(entry.path()).or_else(|_| entry.metadata());
+        Detail {
+            is_symlink,
+            ..metadata
+                .map(|metadata| Self::from(&metadata))
+                .unwrap_or_default()
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Self {
+        fs::metadata(path)
+            .map(|metadata| Self::from(&metadata))
+            .unwrap_or_default()
+    }
+
+    fn from(metadata: &Metadata) -> Self {
+        Detail {
+            size: metadata.len(),
+            modified: metadata.mtime(),
+            mode: metadata.mode(),
+            is_dir: metadata.is_dir(),
+            is_symlink: false,
+        }
+    }
+
+    /// `drwxr-xr-x`形式的权限
+    fn perm(&self) -> String {
+        let kind = match (self.is_symlink, self.is_dir) {
+            (true, _) => 'l',
+            (false, true) => 'd',
+            (false, false) => '-',
+        };
+        let mut perm = String::from(kind);
+        for shift in [6, 3, 0] {
+            let bits = self.mode >> shift;
+            perm.push(if bits & 4 != 0 { 'r' } else { '-' });
+            perm.push(if bits & 2 != 0 { 'w' } else { '-' });
+            perm.push(if bits & 1 != 0 { 'x' } else { '-' });
+        }
+        perm
+    }
+
+    /// `1.5K`形式的大小，目录为`-`
+    fn size(&self) -> String {
+        if self.is_dir {
+            return String::from("-");
+        }
+        const UNITS: [&str; 5] = ["B", "K", "M", "G", "T"];
+        let mut size = self.size as f64;
+        let mut unit = 0;
+        while size >= 1024.0 && unit < UNITS.len() - 1 {
+            size /= 1024.0;
+            unit += 1;
+        }
+        match unit {
+            0 => format!("{}B", self.size),
+            _ if size < 10.0 => format!("{size:.1}{}", UNITS[unit]),
+            _ => format!("{size:.0}{}", UNITS[unit]),
+        }
+    }
+
+    fn time(&self) -> String {
+        let (year, month, day, hour, min, _) = datetime(self.modified);
+        format!("{year:04}-{month:02}-{day:02} {hour:02}:{min:02}")
+    }
+}
+
+/// 显示的列
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Columns {
+    perm: bool,
+    size: bool,
+    time: bool,
+}
+
+impl Columns {
+    /// 宽为`width`的文件树中能显示的列
+    pub fn fit(width: usize) -> Self {
+        let mut rest = width.saturating_sub(NAME_WIDTH);
+        let mut take = |width: usize| {
+            let is_fit = rest > width;
+            if is_fit {
+                rest -= width + 1;
+            }
+            is_fit
+        };
+        let size = take(SIZE_WIDTH);
+        let time = size && take(TIME_WIDTH);
+        let perm = time && take(PERM_WIDTH);
+        Columns { perm, size, time }
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        !self.size
+    }
+
+    /// 所有列的宽度，每列之前有一个空格
+    pub fn width(&self) -> usize {
+        [
+            (self.perm, PERM_WIDTH),
+            (self.size, SIZE_WIDTH),
+            (self.time, TIME_WIDTH),
+        ]
+        .iter()
+        .filter(|(is_show, _)| *is_show)
+        .map(|(_, width)| width + 1)
+        .sum()
+    }
+
+    pub fn render(&self, detail: &Detail) -> String {
+        let mut line = String::new();
+        if self.perm {
+            line += &format!(" {:<PERM_WIDTH$}", detail.perm());
+        }
+        if self.size {
+            line += &format!(" {:>SIZE_WIDTH$}", detail.size());
+        }
+        if self.time {
+            line += &format!(" {:<TIME_WIDTH$}", detail.time());
+        }
+        line
+    }
+}
+
+/// 1970-01-01起的秒数对应的`(年, 月, 日, 时, 分, 秒)`，按UTC计算
+///
+/// 参考: http://howardhinnant.github.io/date_algorithms.html
+pub fn datetime(secs: i64) -> (i64, i64, i64, i64, i64, i64) {
+    let (days, secs) = (secs.div_euclid(86400), secs.rem_euclid(86400));
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day, secs / 3600, secs % 3600 / 60, secs % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn epoch() {
+        assert_eq!(datetime(0), (1970, 1, 1, 0, 0, 0));
+        assert_eq!(datetime(-1), (1969, 12, 31, 23, 59, 59));
+    }
+
+    #[test]
+    fn leap_days() {
+        assert_eq!(datetime(951782400), (2000, 2, 29, 0, 0, 0));
+        assert_eq!(datetime(1709251199), (2024, 2, 29, 23, 59, 59));
+        assert_eq!(datetime(1709251200), (2024, 3, 1, 0, 0, 0));
+    }
+
+    #[test]
+    fn century_years_are_not_leap() {
+        assert_eq!(datetime(4107542400), (2100, 3, 1, 0, 0, 0));
+        assert_eq!(datetime(-2203891200), (1900, 3, 1, 0, 0, 0));
+    }
+}
@@ -0,0 +1,331 @@
+/// 文件树中隐藏的文件：以`.`开头的文件、被忽略规则匹配的文件与排除的文件
+///
+/// 忽略规则来自`.gitignore`、`.ignore`与`.git/info/exclude`，格式与git相同：
+/// - 空行与`#`开头的行被跳过，`!`开头的规则重新包含之前被忽略的文件
+/// - 以`/`结尾的规则只匹配目录
+/// - 含有`/`的规则相对于规则文件所在的目录匹配，否则匹配任意层级的文件名
+/// - `*`、`?`不匹配`/`，`**`可以匹配多层目录，`[a-z]`匹配字符集
+///
+/// 后面的规则优先，子目录中的规则在上级目录之后
+use crate::settings::Settings;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+/// 读取的忽略文件，按优先级从低到高排列
+const IGNORE_FILES: [&str; 3] = [".git/info/exclude", ".gitignore", ".ignore"];
+
+#[derive(Debug, Clone)]
+pub struct Rule {
+    glob: Vec<char>,
+    /// 规则文件所在的目录
+    base: PathBuf,
+    is_negated: bool,
+    is_dir_only: bool,
+    /// 是否相对于`base`匹配
+    is_anchored: bool,
+}
+
+impl Rule {
+    /// 解析规则文件中的一行，空行与注释返回`None`
+    pub fn parse(line: &str, base: &Path) -> Option<Self> {
+        let mut line = line.trim_end_matches('\r');
+        // 行尾的空格被忽略，除非用`\`转义
+        while line.ends_with(' ') && !line.ends_with("\\ ") {
+            line = &line[..line.len() - 1];
+        }
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (is_negated, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let line = match line.strip_prefix('\\') {
+            Some(rest) if rest.starts_with(['#', '!']) => rest,
+            _ => line,
+        };
+        let (is_dir_only, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let is_anchored = line.contains('/');
+        let line = line.strip_prefix('/').unwrap_or(line);
+        if line.is_empty() {
+            return None;
+        }
+        Some(Rule {
+            glob: line.chars().collect(),
+            base: base.to_path_buf(),
+            is_negated,
+            is_dir_only,
+            is_anchored,
+        })
+    }
+
+    /// 匹配时返回是否忽略，不匹配时返回`None`
+    fn matches(&self, path: &Path, is_dir: bool) -> Option<bool> {
+        if self.is_dir_only && !is_dir {
+            return None;
+        }
+        let text = if self.is_anchored {
+            path.strip_prefix(&self.base).ok()?.to_string_lossy()
+        } else {
+            path.file_name()?.to_string_lossy()
+        };
+        let text: Vec<char> = text.chars().collect();
+        glob_match(&self.glob, &text).then_some(!self.is_negated)
+    }
+}
+
+/// 文件树中一个目录使用的过滤条件，子目录在此基础上加入自己的忽略规则
+#[derive(Debug, Clone, Default)]
+pub struct Ignore {
+    is_show_hidden: bool,
+    /// 从仓库的根目录到当前目录的忽略规则，显示被忽略的文件时为`None`
+    rules: Option<Rc<Vec<Rule>>>,
+    exclude: Rc<Vec<Rule>>,
+}
+
+impl Ignore {
+    /// 根目录`root`所在的位置使用的条件，包含其上级目录直到git仓库根目录中的规则
+    pub fn new(root: &Path, settings: &Settings) -> Self {
+        let rules = (!settings.is_show_ignored).then(|| {
+            let mut dirs: Vec<&Path> = Vec::new();
+            let mut is_repo = false;
+            for dir in root.ancestors().skip(1) {
+                dirs.push(dir);
+                if dir.join(".git").exists() {
+                    is_repo = true;
+                    break;
+                }
+            }
+            if !is_repo {
+                dirs.clear();
+            }
+            Rc::new(dirs.into_iter().rev().flat_map(load).collect())
+        });
+        let exclude = settings
+            .exclude
+            .iter()
+            .filter_map(|glob| Rule::parse(glob, root))
+            .collect();
+        Ignore {
+            is_show_hidden: settings.is_show_hidden,
+            rules,
+            exclude: Rc::new(exclude),
+        }
+    }
+
+    /// 目录`dir`中的文件使用的条件，加入`dir`中的忽略规则
+    pub fn child(&self, dir: &Path) -> Self {
+        let rules = self.rules.as_ref().map(|rules| {
+            let own = load(dir);
+            if own.is_empty() {
+                rules.clone()
+            } else {
+                Rc::new(rules.iter().cloned().chain(own).collect())
+            }
+        });
+        Ignore {
+            rules,
+            ..self.clone()
+        }
+    }
+
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let name = path.file_name().unwrap_or_default();
+        if !self.is_show_hidden && name.as_encoded_bytes().starts_with(b".") {
+            return true;
+        }
+        if let Some(rules) = &self.rules {
+            if name == ".git" || is_matched(rules, path, is_dir) {
+                return true;
+            }
+        }
+        is_matched(&self.exclude, path, is_dir)
+    }
+}
+
+/// `path`是否为忽略文件，改变后需要重新读取规则
+pub fn is_rule_file(path: &Path) -> bool {
+    IGNORE_FILES.iter().any(|name| path.ends_with(name))
+}
+
+/// 读取目录中的忽略文件
+fn load(dir: &Path) -> Vec<Rule> {
+    IGNORE_FILES
+        .iter()
+        .filter_map(|name| fs::read_to_string(dir.join(name)).ok())
+        .flat_map(|text| {
+            text.lines()
+                .filter_map(|line| Rule::parse(line, dir))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// 最后一条匹配的规则决定是否忽略
+fn is_matched(rules: &[Rule], path: &Path, is_dir: bool) -> bool {
+    rules
+        .iter()
+        .rev()
+        .find_map(|rule| rule.matches(path, is_dir))
+        .unwrap_or(false)
+}
+
+fn glob_match(glob: &[char], text: &[char]) -> bool {
+    match glob.first() {
+        None => text.is_empty(),
+        Some('*') if glob.get(1) == Some(&'*') => {
+            let rest = &glob[2..];
+            match rest.strip_prefix(&['/']) {
+                // `**/`匹配零或多层目录
+                Some(rest) => {
+                    glob_match(rest, text)
+                        || (0..text.len())
+                            .any(|idx| text[idx] == '/' && glob_match(rest, &text[idx + 1..]))
+                }
+                None => (0..=text.len()).any(|idx| glob_match(rest, &text[idx..])),
+            }
+        }
+        Some('*') => (0..=text.len())
+            .take_while(|&idx| idx == 0 || text[idx - 1] != '/')
+            .any(|idx| glob_match(&glob[1..], &text[idx..])),
+        Some('?') => match text.first() {
+            Some(ch) if *ch != '/' => glob_match(&glob[1..], &text[1..]),
+            _ => false,
+        },
+        Some('[') => match (class_match(&glob[1..], text.first()), text.first()) {
+            (Some((true, len)), Some(_)) => glob_match(&glob[len + 1..], &text[1..]),
+            (Some(_), _) => false,
+            // 没有`]`时作为普通字符
+            (None, Some('[')) => glob_match(&glob[1..], &text[1..]),
+            (None, _) => false,
+        },
+        Some('\\') if glob.len() > 1 => {
+            text.first() == Some(&glob[1]) && glob_match(&glob[2..], &text[1..])
+        }
+        Some(ch) => text.first() == Some(ch) && glob_match(&glob[1..], &text[1..]),
+    }
+}
+
+/// 匹配`[`之后的字符集，返回是否匹配与字符集（含`]`）的长度，没有`]`时返回`None`
+fn class_match(glob: &[char], ch: Option<&char>) -> Option<(bool, usize)> {
+    let (is_negated, start) = match glob.first() {
+        Some('!' | '^') => (true, 1),
+        _ => (false, 0),
+    };
+    let mut idx = start;
+    let mut is_matched = false;
+    loop {
+        match glob.get(idx)? {
+            // 开头的`]`是普通字符
+            ']' if idx > start => break,
+            &low => {
+                let high = match (glob.get(idx + 1), glob.get(idx + 2)) {
+                    (Some('-'), Some(&high)) if high != ']' => {
+                        idx += 2;
+                        high
+                    }
+                    _ => low,
+                };
+                if ch.is_some_and(|ch| (low..=high).contains(ch)) {
+                    is_matched = true;
+                }
+                idx += 1;
+            }
+        }
+    }
+    Some((is_matched != is_negated && ch != Some(&'/'), idx + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(glob: &str, text: &str) -> bool {
+        let (glob, text): (Vec<char>, Vec<char>) = (glob.chars().collect(), text.chars().collect());
+        glob_match(&glob, &text)
+    }
+
+    fn rule(line: &str) -> Rule {
+        Rule::parse(line, Path::new("/repo")).unwrap()
+    }
+
+    #[test]
+    fn double_star_matches_zero_or_more_dirs() {
+        assert!(matches("**/foo", "foo"));
+        assert!(matches("**/foo", "a/b/foo"));
+        assert!(matches("a/**/b", "a/b"));
+        assert!(matches("a/**/b", "a/x/y/b"));
+        assert!(!matches("a/**/b", "ab"));
+        assert!(matches("a/**", "a/x/y"));
+    }
+
+    #[test]
+    fn star_and_question_do_not_match_slash() {
+        assert!(matches("*.rs", "main.rs"));
+        assert!(!matches("*.rs", "src/main.rs"));
+        assert!(matches("a?c", "abc"));
+        assert!(!matches("a?c", "a/c"));
+    }
+
+    #[test]
+    fn character_classes() {
+        assert!(matches("[a-z]1", "b1"));
+        assert!(!matches("[a-z]1", "B1"));
+        assert!(matches("[!a-z]1", "B1"));
+        assert!(!matches("[!a-z]1", "b1"));
+        assert!(matches("[^a-z]", "_"));
+        assert!(matches("[]a]", "]"));
+        assert!(!matches("[!a]", "/"));
+        // 没有`]`时`[`是普通字符
+        assert!(matches("[a", "[a"));
+    }
+
+    #[test]
+    fn class_match_returns_length() {
+        let glob: Vec<char> = "!a-z]rest".chars().collect();
+        assert_eq!(class_match(&glob, Some(&'A')), Some((true, 5)));
+        assert_eq!(class_match(&glob, Some(&'q')), Some((false, 5)));
+        assert_eq!(class_match(&['a', 'b'], Some(&'a')), None);
+    }
+
+    #[test]
+    fn escaped_characters() {
+        assert!(matches("\\*", "*"));
+        assert!(!matches("\\*", "a"));
+        let comment = rule("\\#notes");
+        assert_eq!(comment.glob, "#notes".chars().collect::<Vec<_>>());
+        assert!(!comment.is_negated);
+        assert!(rule("\\!keep").glob.starts_with(&['!']));
+        assert!(Rule::parse("#comment", Path::new("/repo")).is_none());
+    }
+
+    #[test]
+    fn parse_flags() {
+        let dir = rule("a/");
+        assert!(dir.is_dir_only && !dir.is_anchored);
+        let anchored = rule("/a");
+        assert!(anchored.is_anchored && anchored.glob == ['a']);
+        assert!(rule("a/b").is_anchored);
+        assert!(rule("!a").is_negated);
+        assert_eq!(rule("a \\ ").glob, "a \\ ".chars().collect::<Vec<_>>());
+        assert_eq!(rule("a  ").glob, ['a']);
+        assert!(Rule::parse("", Path::new("/repo")).is_none());
+        assert!(Rule::parse("/", Path::new("/repo")).is_none());
+    }
+
+    #[test]
+    fn unanchored_dir_rule_matches_at_any_depth() {
+        let dir = rule("build/");
+        assert_eq!(dir.matches(Path::new("/repo/a/build"), true), Some(true));
+        assert_eq!(dir.matches(Path::new("/repo/a/build"), false), None);
+        let anchored = rule("/build");
+        assert_eq!(anchored.matches(Path::new("/repo/build"), true), Some(true));
+        assert_eq!(anchored.matches(Path::new("/repo/a/build"), true), None);
+    }
+}
@@ -0,0 +1,54 @@
+/// 文件树的文件操作中用到的复制与移动
+use std::{
+    fs, io,
+    os::unix::fs::symlink,
+    path::{Path, PathBuf},
+};
+
+/// 复制文件或整个目录，符号链接复制为指向相同目标的链接
+pub fn copy_all(from: &Path, to: &Path) -> io::Result<()> {
+    let file_type = fs::symlink_metadata(from)?.file_type();
+    if file_type.is_symlink() {
+        symlink(fs::read_link(from)?, to)
+    } else if file_type.is_dir() {
+        fs::create_dir(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_all(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        fs::copy(from, to).map(|_| ())
+    }
+}
+
+/// 移动文件或目录，跨文件系统时先复制再删除原来的
+pub fn move_all(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::rename(from, to) {
+        Err(err) if err.kind() == io::ErrorKind::CrossesDevices => {
+            copy_all(from, to)?;
+            if fs::symlink_metadata(from)?.is_dir() {
+                fs::remove_dir_all(from)
+            } else {
+                fs::remove_file(from)
+            }
+        }
+        ret => ret,
+    }
+}
+
+/// 副本的路径，取`a_copy.txt`、`a_copy2.txt`……中第一个不存在的
+pub fn copy_path(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let (stem, ext) = match name.rfind('.') {
+        Some(idx) if idx > 0 => name.split_at(idx),
+        _ => (&name[..], ""),
+    };
+    (1..)
+        .map(|cnt| match cnt {
+            1 => path.with_file_name(format!("{stem}_copy{ext}")),
+            cnt => path.with_file_name(format!("{stem}_copy{cnt}{ext}")),
+        })
+        .find(|path| path.symlink_metadata().is_err())
+        .unwrap()
+}
@@ -0,0 +1,132 @@
+/// 文件树的排序：目录在前，其余按设置的方式，相同时按名字的自然顺序
+use super::DirItem;
+use crate::settings::{Settings, SortBy};
+use std::{cmp::Ordering, iter::Peekable, path::Path, str::Chars};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Sort {
+    by: SortBy,
+    /// 是否区分大小写
+    is_case: bool,
+}
+
+impl Sort {
+    pub fn new(settings: &Settings) -> Self {
+        Sort {
+            by: settings.sort_by,
+            is_case: settings.is_sort_case,
+        }
+    }
+
+    pub fn sort(&self, items: &mut [DirItem]) {
+        items.sort_by(|a, b| {
+            let (a_dir, b_dir) = (matches!(a, DirItem::Dir(_)), matches!(b, DirItem::Dir(_)));
+            let (a_detail, b_detail) = (a.detail(), b.detail());
+            let ord = match self.by {
+                SortBy::Name => Ordering::Equal,
+                // 目录的大小没有意义
+                SortBy::Size if a_dir && b_dir => Ordering::Equal,
+                SortBy::Size => b_detail.size.cmp(&a_detail.size),
+                SortBy::Time => b_detail.modified.cmp(&a_detail.modified),
+                SortBy::Ext => self.cmp(&extension(a.path()), &extension(b.path())),
+            };
+            b_dir
+                .cmp(&a_dir)
+                .then(ord)
+                .then_with(|| self.cmp(&name(a.path()), &name(b.path())))
+                // 不区分大小写时相同的名字也有确定的顺序
+                .then_with(|| natural_cmp(&name(a.path()), &name(b.path()), true))
+        });
+    }
+
+    #[inline]
+    fn cmp(&self, a: &str, b: &str) -> Ordering {
+        natural_cmp(a, b, self.is_case)
+    }
+}
+
+#[inline]
+fn name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// 扩展名，`.`开头的文件没有扩展名
+#[inline]
+fn extension(path: &Path) -> String {
+    path.extension()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// 自然顺序：连续的数字按数值比较，数值相同时前导零少的在前
+fn natural_cmp(a: &str, b: &str, is_case: bool) -> Ordering {
+    let (mut a, mut b) = (a.chars().peekable(), b.chars().peekable());
+    loop {
+        let ord = match (a.peek(), b.peek()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let (x, y) = (digits(&mut a), digits(&mut b));
+                let (x_num, y_num) = (x.trim_start_matches('0'), y.trim_start_matches('0'));
+                x_num
+                    .len()
+                    .cmp(&y_num.len())
+                    .then_with(|| x_num.cmp(y_num))
+                    .then_with(|| x.len().cmp(&y.len()))
+            }
+            (Some(&x), Some(&y)) => {
+                a.next();
+                b.next();
+                if is_case {
+                    x.cmp(&y)
+                } else {
+                    x.to_lowercase().cmp(y.to_lowercase())
+                }
+            }
+        };
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+}
+
+fn digits(chars: &mut Peekable<Chars>) -> String {
+    let mut digits = String::new();
+    while let Some(ch) = chars.next_if(char::is_ascii_digit) {
+        digits.push(ch);
+    }
+    digits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_compare_by_value() {
+        assert_eq!(natural_cmp("a2", "a10", true), Ordering::Less);
+        assert_eq!(natural_cmp("a10", "a2", true), Ordering::Greater);
+        assert_eq!(natural_cmp("a10b", "a10c", true), Ordering::Less);
+        assert_eq!(natural_cmp("v1.9", "v1.10", true), Ordering::Less);
+        assert_eq!(natural_cmp("a", "a1", true), Ordering::Less);
+    }
+
+    #[test]
+    fn fewer_leading_zeros_first() {
+        assert_eq!(natural_cmp("a1", "a01", true), Ordering::Less);
+        assert_eq!(natural_cmp("a007", "a8", true), Ordering::Less);
+        assert_eq!(natural_cmp("a01", "a01", true), Ordering::Equal);
+    }
+
+    #[test]
+    fn case_sensitivity() {
+        assert_eq!(natural_cmp("B", "a", true), Ordering::Less);
+        assert_eq!(natural_cmp("B", "a", false), Ordering::Greater);
+        assert_eq!(natural_cmp("Abc", "abc", false), Ordering::Equal);
+    }
+}
@@ -0,0 +1,298 @@
+/// 按照XDG回收站规范把文件或目录移到回收站
+///
+/// 与家目录在同一文件系统上时使用`$XDG_DATA_HOME/Trash`（默认为`~/.local/share/Trash`），
+/// 否则使用所在文件系统挂载点下的`.Trash-$uid`
+///
+/// 文件移到回收站的`files`下，`info`下同名的`.trashinfo`记录原来的路径与删除的时间，
+/// 文件管理器可以据此还原；规范要求删除时间为本地时间，时区从`TZ`或`/etc/localtime`读取
+///
+/// 参考: https://specifications.freedesktop.org/trash-spec/latest/
+use super::detail::datetime;
+use std::{
+    ffi::OsStr,
+    fs::{self, DirBuilder, OpenOptions},
+    io::{self, Write},
+    os::unix::{
+        ffi::OsStrExt,
+        fs::{DirBuilderExt, MetadataExt},
+    },
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// 把绝对路径`path`移到回收站，返回在回收站中的路径
+pub fn trash(path: &Path) -> io::Result<PathBuf> {
+    let dev = fs::symlink_metadata(path)?.dev();
+    let home = home_trash()?;
+    create_trash(&home)?;
+    if fs::metadata(&home)?.dev() == dev {
+        return move_to(path, &home, None);
+    }
+    let top = mount_point(path, dev)?;
+    // 进程目录的所有者即当前用户
+    let uid = fs::metadata("/proc/self")?.uid();
+    let trash = top.join(format!(".Trash-{uid}"));
+    create_trash(&trash)?;
+    move_to(path, &trash, Some(&top))
+}
+
+fn home_trash() -> io::Result<PathBuf> {
+    let data = match std::env::var_os("XDG_DATA_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => match std::env::var_os("HOME") {
+            Some(home) => PathBuf::from(home).join(".local/share"),
+            None => return Err(io::Error::new(io::ErrorKind::NotFound, "No Home Directory")),
+        },
+    };
+    Ok(data.join("Trash"))
+}
+
+/// 创建回收站的目录，只有当前用户可以访问
+fn create_trash(trash: &Path) -> io::Result<()> {
+    let mut builder = DirBuilder::new();
+    builder.recursive(true).mode(0o700);
+    builder.create(trash.join("files"))?;
+    builder.create(trash.join("info"))
+}
+
+/// `path`所在文件系统的挂载点，`dev`为其设备号
+fn mount_point(path: &Path, dev: u64) -> io::Result<PathBuf> {
+    let mut top = path.parent().unwrap_or(path);
+    while let Some(parent) = top.parent() {
+        if fs::metadata(parent)?.dev() != dev {
+            break;
+        }
+        top = parent;
+    }
+    Ok(top.to_path_buf())
+}
+
+/// 移到回收站`trash`中，`top`为挂载点时记录相对于它的路径
+fn move_to(path: &Path, trash: &Path, top: Option<&Path>) -> io::Result<PathBuf> {
+    let name = match path.file_name() {
+        Some(name) => name,
+        None => return Err(io::Error::new(io::ErrorKind::InvalidInput, "No File Name")),
+    };
+    let orig = top
+        .and_then(|top| path.strip_prefix(top).ok())
+        .unwrap_or(path);
+    let content = format!(
+        "[Trash Info]\nPath={}\nDeletionDate={}\n",
+        encode(orig.as_os_str()),
+        deletion_date()
+    );
+
+    // 先独占地创建`.trashinfo`来占用名字，再移动文件
+    let mut cnt = 1;
+    loop {
+        let trash_name = numbered(name, cnt);
+        let info = trash.join("info").join(format!("{trash_name}.trashinfo"));
+        let dest = trash.join("files").join(&trash_name);
+        cnt += 1;
+        match OpenOptions::new().write(true).create_new(true).open(&info) {
+            Ok(mut file) => {
+                let ret = if dest.symlink_metadata().is_ok() {
+                    None
+                } else {
+                    Some(
+                        file.write_all(content.as_bytes())
+                            .and_then(|_| fs::rename(path, &dest)),
+                    )
+                };
+                match ret {
+                    Some(Ok(())) => return Ok(dest),
+                    Some(Err(err)) => {
+                        let _ = fs::remove_file(&info);
+                        return Err(err);
+                    }
+                    // `files`中有同名但没有记录的文件
+                    None => fs::remove_file(&info)?,
+                }
+            }
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => (),
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// 第`cnt`个候选的名字：`a.txt`、`a.2.txt`、`a.3.txt`……
+fn numbered(name: &OsStr, cnt: usize) -> String {
+    let name = name.to_string_lossy();
+    if cnt == 1 {
+        return name.into_owned();
+    }
+    match name.rfind('.') {
+        Some(idx) if idx > 0 => format!("{}.{cnt}{}", &name[..idx], &name[idx..]),
+        _ => format!("{name}.{cnt}"),
+    }
+}
+
+/// 按URL的方式转义路径，保留`/`
+fn encode(path: &OsStr) -> String {
+    let mut encoded = String::new();
+    for &byte in path.as_bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+/// 当前的本地时间，格式为`YYYY-MM-DDThh:mm:ss`
+fn deletion_date() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs()) as i64;
+    let (year, month, day, hour, min, sec) = datetime(secs + utc_offset(secs));
+    format!("{year:04}-{month:02}-{day:02}T{hour:02}:{min:02}:{sec:02}")
+}
+
+/// `secs`时本地时间相对于UTC的偏移（秒），无法确定时区时按UTC
+///
+/// `TZ`可以是时区文件的名字（如`Asia/Shanghai`）、绝对路径或不含夏令时的POSIX格式（如`CST-8`），
+/// 没有设置时读取`/etc/localtime`
+fn utc_offset(secs: i64) -> i64 {
+    let tz = std::env::var("TZ").unwrap_or_default();
+    let name = tz.strip_prefix(':').unwrap_or(&tz);
+    let path = match name {
+        "" => PathBuf::from("/etc/localtime"),
+        name if name.starts_with('/') => PathBuf::from(name),
+        name => Path::new("/usr/share/zoneinfo").join(name),
+    };
+    fs::read(path)
+        .ok()
+        .and_then(|data| tzif_offset(&data, secs))
+        .or_else(|| posix_offset(name))
+        .unwrap_or(0)
+}
+
+/// 从TZif格式的时区文件中取出`secs`时的偏移
+///
+/// 取`secs`之前最后一次变化后的偏移；之后不再变化时优先使用文件末尾的POSIX格式，
+/// 其中含有夏令时规则时不支持，仍沿用最后一次变化后的偏移
+///
+/// 参考: https://www.man7.org/linux/man-pages/man5/tzfile.5.html
+fn tzif_offset(data: &[u8], secs: i64) -> Option<i64> {
+    // 头部的6个计数：isutcnt、isstdcnt、leapcnt、timecnt、typecnt、charcnt
+    let counts = |data: &[u8]| -> Option<[usize; 6]> {
+        if data.get(..4)? != b"TZif" {
+            return None;
+        }
+        let mut counts = [0; 6];
+        for (idx, cnt) in counts.iter_mut().enumerate() {
+            let bytes = data.get(20 + idx * 4..24 + idx * 4)?;
+            *cnt = u32::from_be_bytes(bytes.try_into().ok()?) as usize;
+        }
+        Some(counts)
+    };
+    let [isut, isstd, leap, time, types, chars] = counts(data)?;
+    // 版本2之后第一部分的32位数据只为兼容，使用之后的64位数据
+    let (data, size) = match data.get(4)? {
+        b'2'.. => {
+            let len = 44 + time * 5 + types * 6 + chars + leap * 8 + isstd + isut;
+            (data.get(len..)?, 8)
+        }
+        _ => (data, 4),
+    };
+    let [isut, isstd, leap, time, types, chars] = counts(data)?;
+    let body = data.get(44..)?;
+    let at = |idx: usize| -> Option<i64> {
+        let bytes = body.get(idx * size..(idx + 1) * size)?;
+        Some(match size {
+            8 => i64::from_be_bytes(bytes.try_into().ok()?),
+            _ => i32::from_be_bytes(bytes.try_into().ok()?) as i64,
+        })
+    };
+    let mut passed = 0;
+    while passed < time && at(passed)? <= secs {
+        passed += 1;
+    }
+    if passed == time && size == 8 {
+        let len = 44 + time * 9 + types * 6 + chars + leap * 12 + isstd + isut;
+        let footer = data.get(len..).unwrap_or_default();
+        let footer = String::from_utf8_lossy(footer);
+        if let Some(offset) = posix_offset(footer.trim()) {
+            return Some(offset);
+        }
+    }
+    let kind = match passed {
+        0 => 0,
+        passed => *body.get(time * size + passed - 1)? as usize,
+    };
+    let info = body.get(time * (size + 1) + kind * 6..)?.get(..4)?;
+    Some(i32::from_be_bytes(info.try_into().ok()?) as i64)
+}
+
+/// 不含夏令时的POSIX格式的时区的偏移，如`CST-8`、`<+0530>-5:30`、`UTC0`
+///
+/// 其中的偏移为本地时间到UTC的差，与通常的写法符号相反
+fn posix_offset(tz: &str) -> Option<i64> {
+    let rest = match tz.strip_prefix('<') {
+        Some(rest) => &rest[rest.find('>')? + 1..],
+        None => tz.trim_start_matches(|ch: char| ch.is_ascii_alphabetic()),
+    };
+    if rest.len() == tz.len() {
+        return None;
+    }
+    let (sign, rest) = match rest.strip_prefix('-') {
+        Some(rest) => (1, rest),
+        None => (-1, rest.strip_prefix('+').unwrap_or(rest)),
+    };
+    let mut offset = 0;
+    let mut parts = 0;
+    for (part, unit) in rest.split(':').zip([3600, 60, 1]) {
+        offset += part.parse::<u32>().ok()? as i64 * unit;
+        parts += 1;
+    }
+    // 后面还有夏令时的名字或规则
+    if parts < rest.split(':').count() {
+        return None;
+    }
+    Some(sign * offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 版本1的时区文件：`times`时刻切换到`types`中对应的偏移
+    fn tzif(times: &[(i32, u8)], types: &[i32]) -> Vec<u8> {
+        let mut data = b"TZif".to_vec();
+        data.resize(20, 0);
+        for cnt in [0, 0, 0, times.len(), types.len(), 4] {
+            data.extend((cnt as u32).to_be_bytes());
+        }
+        data.extend(times.iter().flat_map(|(time, _)| time.to_be_bytes()));
+        data.extend(times.iter().map(|(_, kind)| kind));
+        for offset in types {
+            data.extend(offset.to_be_bytes());
+            data.extend([0, 0]);
+        }
+        data.extend(b"UTC\0");
+        data
+    }
+
+    #[test]
+    fn posix_offsets() {
+        assert_eq!(posix_offset("UTC0"), Some(0));
+        assert_eq!(posix_offset("CST-8"), Some(8 * 3600));
+        assert_eq!(posix_offset("EST5"), Some(-5 * 3600));
+        assert_eq!(posix_offset("<+0530>-5:30"), Some(5 * 3600 + 30 * 60));
+        assert_eq!(posix_offset("EST5EDT,M3.2.0,M11.1.0"), None);
+        assert_eq!(posix_offset("UTC"), None);
+        assert_eq!(posix_offset("8"), None);
+    }
+
+    #[test]
+    fn tzif_uses_last_transition() {
+        let data = tzif(&[(1000, 1), (2000, 0)], &[3600, 7200]);
+        assert_eq!(tzif_offset(&data, 0), Some(3600));
+        assert_eq!(tzif_offset(&data, 1000), Some(7200));
+        assert_eq!(tzif_offset(&data, 1999), Some(7200));
+        assert_eq!(tzif_offset(&data, 5000), Some(3600));
+        assert_eq!(tzif_offset(b"not a tzif file", 0), None);
+    }
+}
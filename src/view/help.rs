@@ -27,8 +27,12 @@ impl View for Help {
 
         The line will wrap if the line's length is over the max length
 
+        <Enter> keeps the indentation of the previous line and indents one
+        more level after an opening bracket
+
     3. How to Exit
-        Press <Esc> to exit the editor
+        Press <Esc> to exit the editor, modified files ask to save: [Y]es, [N]o
+        or [C]ancel (also <Left>/<Right> and <Enter>)
         Press <F1~5> to exit the help
 
     4. Save
@@ -41,7 +45,12 @@ impl View for Help {
         Press <Ctrl+f> again to replace the chosen string
 
     6. Terminal Help
-        Input `tged --help` for more information"#;
+        Input `tged --help` for more information
+        Open a file at a line with `tged file:line[:col]` or `tged +N file`
+        Open a directory as the file tree root with `tged dir`, several
+        directories open a workspace with one top-level node per directory
+        Edit piped input with `cmd | tged -`
+        Use `cmd | tged --pipe | cmd` to filter the text through the editor"#;
 
         let page2 = r#" View Help
 
@@ -50,15 +59,62 @@ impl View for Help {
         Press <F3>: shift to File Tree
         Press <F4>: shift to Menu
         Press <F5>: shift the view in order
+        Press <F9>: start/stop recording a keyboard macro, then name it
+        Press <F10>: play a macro, fill in its name and count (<Tab> to switch)
+        Press <Alt+x>: open the command palette to search and run any action
 
     2. FileTree
         Press <Enter>: open the directory or open the file
+        Expanded directories are watched, a change reloads only that directory
+        and keeps the expanded directories and the selection
+        Press <a>/<A>: create a file/directory, `/` in the name creates subdirectories
+        Press <r>: rename, <d>: duplicate in the same directory
+        Press <m>/<c>: move/copy to a path chosen in the file dialog
+        Press <Delete>: move to the trash after confirming
+        Press <H>/<I>: show or hide dotfiles/files ignored by .gitignore and .ignore
+        Press </>: filter the tree by name, <Enter> to go to the selected item,
+        <Backspace> on an empty filter to cancel
+        Press <s>: sort by name, size, modified time or extension in turn,
+        names sort naturally (`a2` before `a10`)
+        Widen the tree with <Alt+Left>/<Alt+Right> in MainView to show the size,
+        modified time (UTC) and permissions
+        Press <f>: reveal the current file, `set follow on` to do it on every
+        file switch
+        Open files follow a rename or a move
 
     3. MainView
         Press <Alt+Left>/<Alt+Right>: resize the view
         Press <F6>: shift the file
         Press <F7>: shift the file reversely
-        Press <F8>: shift the file according to the input
+        Press <F8>: choose a file from the open files, most recent first
+        Press <Ctrl+p>: find a file by fuzzy matching, <Enter> to open it
+        Press <Ctrl+o>: open a file from the file dialog
+        Press <Ctrl+s>: save, an unnamed file asks for a path in the file dialog
+        Press <Ctrl+w>: close the file, a modified file asks to save (y/n/c)
+        Press <Ctrl+g>: go to `line[:col]`, `+N`/`-N` lines or `N%` of the file
+        Press <Ctrl+]>: jump to the matching bracket
+        Press <Ctrl+Left>/<Ctrl+Right>: move by word
+        Press <Ctrl+Alt+Left>/<Ctrl+Alt+Right>: move by camelCase/snake_case part
+        Press <Ctrl+Backspace>/<Ctrl+Delete>: delete by word
+        Press <Delete>: delete the character under the cursor
+        Press <Insert>: toggle insert/overwrite mode
+        Hold <Shift> while moving the cursor to select
+        Press <Ctrl+z>/<Ctrl+y>: undo/redo
+        Press <Ctrl+c>/<Ctrl+x>/<Ctrl+v>: copy/cut/paste (the whole line without selection)
+        Press <Ctrl+d>: select the word, press again to add a caret at the next occurrence
+        Press <Alt+l>: add carets at every match of the last search
+        Press <Ctrl+Alt+Up>/<Ctrl+Alt+Down>: add a caret above/below
+        Press <Alt+c>: keep only the main caret
+        Press <Alt+Shift+Arrows> or drag with <Alt>/<Ctrl>: block selection
+        Block copy/cut/paste is rectangular, short lines are padded with spaces
+        Click to move the cursor, drag to select
+        Press <Alt+d>: duplicate the line(s)
+        Press <Ctrl+k>: delete the line(s)
+        Press <Alt+Up>/<Alt+Down>: move the line(s) up/down
+        Press <Alt+j>: join the line(s)
+        Press <Alt+s>/<Alt+S>/<Alt+n>: sort lines ascending/descending/numerically
+        Press <Alt+u>/<Alt+r>/<Alt+h>: unique/reverse/shuffle lines
+        Sort, unique, reverse and shuffle need a selection
 
     4. Menu
         See `Menu Help`"#;
@@ -67,16 +123,50 @@ impl View for Help {
     1. Input
         When focused, You can input command in the bar and press <Enter> to commit it
         It will act as a shell
+        Press <Home>/<End> (<Ctrl+a>/<Ctrl+e>), <Ctrl+Left>/<Ctrl+Right> to move
+        Press <Ctrl+w>/<Alt+d> to delete a word, <Ctrl+u>/<Ctrl+k> to delete to
+        the start/end, <Ctrl+y> to paste what was deleted, also in pop-up inputs
+        Press <Up>/<Down> to browse the command history, saved across sessions
+        Press <Tab> to complete commands, options, themes and paths, press it
+        again (or <Shift+Tab>) to walk through the list, <Enter> to accept
 
     2. Display
         When not being focused, It will display some messages about current state
 
     3. Allowed Command
-        `quit`: to quit
+        `quit`, `help`
+
+        `save`, `write [path]`, `saveas [path]`: save the file, `write` with a
+        path writes a copy without renaming the file
+
+        `open [path]`, `reload`, `cd [dir]`: `saveas` and `open` without a
+        path show the file dialog
+
+        `set [option] [value]`: show or change `indent`, `autopair`, `number`,
+        `hidden`, `ignored`, `exclude` (comma-separated globs), `sort`
+        (`name`/`size`/`time`/`ext`), `sortcase`, `details` and `follow`
+
+        `theme [name]`: list or switch the theme
+
+        `duplicate`, `deleteline`, `moveup`, `movedown`, `join`:
+        edit the current line or the selected lines
+
+        `sort`, `sortdesc`, `sortnum`, `unique`, `reverse`, `shuffle`:
+        reorder the selected lines
+
+        `goto <line>[:<col>]`: go to the position, `+N`/`-N`/`N%` also work
+
+        `macro <name> [lines|matches]`: play the macro at the start of every
+        selected line or at every match of the last search
+
+        `close`, `closeothers`, `closeall`: close the files, other files keep
+        their numbers
 
-        `save`: to save current file
+        `!<cmd>`: run a shell command, a multi-line output opens in a new file
 
-        there will be more commands in the future"#;
+    4. Syntax
+        Quote arguments with spaces, `\` escapes the next character
+        Commands can be shortened to a unique prefix, or `q`, `w`, `s`, `e`, `o`"#;
 
         let page4 = r#"
 
@@ -92,6 +182,7 @@ impl View for Help {
 
         Copyright (c) 2025 NazrinDuck. All Rights Reserved."#;
 
+        self.content.clear();
         self.content.push(String::from(page1));
         self.content.push(String::from(page2));
         self.content.push(String::from(page3));
@@ -0,0 +1,296 @@
+/// LineEdit是单行的输入框，供Menu、MsgBox与Picker等需要输入的视图使用
+///
+/// 内容按字符存储，光标与滚动都以字符为单位，中日韩文字等宽字符占两列
+///
+/// 支持的按键：
+/// - <Left>/<Right>、<Home>/<End>（<Ctrl+a>/<Ctrl+e>）：移动光标
+/// - <Ctrl+Left>/<Ctrl+Right>（<Alt+b>/<Alt+f>）：按单词移动
+/// - <Backspace>/<Delete>：删除一个字符
+/// - <Ctrl+w>（<Ctrl+Backspace>）/<Alt+d>：删除前/后一个单词
+/// - <Ctrl+u>/<Ctrl+k>：删除到开头/末尾
+/// - <Ctrl+y>：粘贴最近删除的单词或片段
+/// - <Up>/<Down>：浏览历史，需要通过`history`开启
+///
+/// 内容超出宽度时会水平滚动，保证光标可见
+use crate::terminal::key::{self, Mods};
+use getch_rs::Key;
+use std::ops::Range;
+
+mod history;
+
+use history::History;
+
+/// 按键的效果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edited {
+    /// 内容改变
+    Text,
+    /// 只移动了光标
+    Cursor,
+    /// 不处理的按键
+    Nothing,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LineEdit {
+    chars: Vec<char>,
+    /// 光标所在的字符下标
+    idx: usize,
+    /// 显示的第一个字符的下标
+    scroll: usize,
+    /// 显示的宽度，为0时不滚动
+    width: usize,
+    /// 最近删除的内容
+    killed: String,
+    history: Option<History>,
+}
+
+impl LineEdit {
+    /// 开启历史，保存在配置目录下名为`name`的文件中
+    pub fn history(&mut self, name: &str) {
+        self.history = Some(History::load(name));
+    }
+
+    #[inline]
+    pub fn text(&self) -> String {
+        self.chars.iter().collect()
+    }
+
+    /// 光标之前的内容
+    #[inline]
+    pub fn before_cursor(&self) -> String {
+        self.chars[..self.idx].iter().collect()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.chars.is_empty()
+    }
+
+    #[inline]
+    pub fn cursor(&self) -> usize {
+        self.idx
+    }
+
+    /// 替换内容，光标移到末尾
+    pub fn set_text(&mut self, text: &str) {
+        self.chars = text.chars().collect();
+        self.idx = self.chars.len();
+        self.fit();
+    }
+
+    pub fn clear(&mut self) {
+        self.set_text("");
+    }
+
+    /// 提交输入：记录到历史并清空，返回输入的内容
+    pub fn submit(&mut self) -> String {
+        let text = self.text();
+        if let Some(history) = &mut self.history {
+            history.reset();
+            if !text.trim().is_empty() {
+                // 写入失败时只是不保存历史
+                let _ = history.push(text.trim());
+            }
+        }
+        self.clear();
+        text
+    }
+
+    /// 用`text`替换字符下标在`range`中的内容，光标移到替换的内容之后
+    pub fn replace(&mut self, range: Range<usize>, text: &str) {
+        self.idx = range.start + text.chars().count();
+        self.chars.splice(range, text.chars());
+        self.fit();
+    }
+
+    /// 设置显示的宽度
+    pub fn set_width(&mut self, width: usize) {
+        if self.width != width {
+            self.width = width;
+            self.fit();
+        }
+    }
+
+    pub fn handle(&mut self, key: &Key) -> Edited {
+        let len = self.chars.len();
+        let edited = match key {
+            Key::Char(ch) if !ch.is_control() => {
+                self.chars.insert(self.idx, *ch);
+                self.idx += 1;
+                Edited::Text
+            }
+            Key::Backspace if self.idx > 0 => {
+                self.idx -= 1;
+                self.chars.remove(self.idx);
+                Edited::Text
+            }
+            Key::Delete if self.idx < len => {
+                self.chars.remove(self.idx);
+                Edited::Text
+            }
+            Key::Left if self.idx > 0 => self.move_to(self.idx - 1),
+            Key::Right if self.idx < len => self.move_to(self.idx + 1),
+            Key::Home | Key::Ctrl('a') => self.move_to(0),
+            Key::End | Key::Ctrl('e') => self.move_to(len),
+            Key::Alt('b') => self.move_to(self.word_start()),
+            Key::Alt('f') => self.move_to(self.word_end()),
+            // Ctrl+Backspace（终端发送^H）
+            Key::Ctrl('w') | Key::Ctrl('h') => self.kill(self.word_start()..self.idx),
+            Key::Alt('d') => self.kill(self.idx..self.word_end()),
+            Key::Ctrl('u') => self.kill(0..self.idx),
+            Key::Ctrl('k') => self.kill(self.idx..len),
+            Key::Ctrl('y') if !self.killed.is_empty() => {
+                let killed = self.killed.clone();
+                self.replace(self.idx..self.idx, &killed);
+                Edited::Text
+            }
+            Key::Up => self.browse(true),
+            Key::Down => self.browse(false),
+            key @ Key::Other(_) => match key::decode(key) {
+                Some((Key::Left, Mods::CTRL)) => self.move_to(self.word_start()),
+                Some((Key::Right, Mods::CTRL)) => self.move_to(self.word_end()),
+                Some((Key::Delete, Mods::CTRL)) => self.kill(self.idx..self.word_end()),
+                _ => Edited::Nothing,
+            },
+            _ => Edited::Nothing,
+        };
+        // 修改内容后结束浏览历史
+        if edited == Edited::Text && !matches!(key, Key::Up | Key::Down) {
+            if let Some(history) = &mut self.history {
+                history.reset();
+            }
+        }
+        if edited != Edited::Nothing {
+            self.fit();
+        }
+        edited
+    }
+
+    /// 显示的内容，不足宽度时以空格补齐
+    pub fn render(&self) -> String {
+        let mut line = String::new();
+        let mut used = 0;
+        for ch in &self.chars[self.scroll..] {
+            let width = char_width(*ch);
+            if self.width > 0 && used + width > self.width {
+                break;
+            }
+            line.push(*ch);
+            used += width;
+        }
+        line + &" ".repeat(self.width.saturating_sub(used))
+    }
+
+    /// 字符下标`idx`处在显示内容中的列，在显示范围之前时为0
+    pub fn col(&self, idx: usize) -> usize {
+        let idx = idx.min(self.chars.len());
+        self.chars
+            .get(self.scroll..idx)
+            .map_or(0, |chars| chars.iter().map(|ch| char_width(*ch)).sum())
+    }
+
+    /// 光标在显示内容中的列
+    #[inline]
+    pub fn cursor_col(&self) -> usize {
+        self.col(self.idx)
+    }
+
+    #[inline]
+    fn move_to(&mut self, idx: usize) -> Edited {
+        self.idx = idx;
+        Edited::Cursor
+    }
+
+    /// 删除`range`中的内容并保存，用于<Ctrl+y>粘贴
+    fn kill(&mut self, range: Range<usize>) -> Edited {
+        if range.is_empty() {
+            return Edited::Nothing;
+        }
+        self.idx = range.start;
+        self.killed = self.chars.drain(range).collect();
+        Edited::Text
+    }
+
+    fn browse(&mut self, is_prev: bool) -> Edited {
+        let input = self.text();
+        let entry = match &mut self.history {
+            Some(history) if is_prev => history.prev(&input),
+            Some(history) => history.next(),
+            None => None,
+        };
+        match entry.map(String::from) {
+            Some(entry) => {
+                self.chars = entry.chars().collect();
+                self.idx = self.chars.len();
+                Edited::Text
+            }
+            None => Edited::Nothing,
+        }
+    }
+
+    /// 光标前一个单词的开头
+    fn word_start(&self) -> usize {
+        let mut idx = self.idx;
+        while idx > 0 && !is_word(self.chars[idx - 1]) {
+            idx -= 1;
+        }
+        while idx > 0 && is_word(self.chars[idx - 1]) {
+            idx -= 1;
+        }
+        idx
+    }
+
+    /// 光标后一个单词的末尾
+    fn word_end(&self) -> usize {
+        let len = self.chars.len();
+        let mut idx = self.idx;
+        while idx < len && !is_word(self.chars[idx]) {
+            idx += 1;
+        }
+        while idx < len && is_word(self.chars[idx]) {
+            idx += 1;
+        }
+        idx
+    }
+
+    /// 调整滚动，使光标可见
+    fn fit(&mut self) {
+        self.idx = self.idx.min(self.chars.len());
+        if self.width == 0 {
+            self.scroll = 0;
+            return;
+        }
+        // 光标在最后时需要多留出一列
+        if self.idx < self.scroll {
+            self.scroll = self.idx;
+        }
+        while self.scroll < self.idx && self.col(self.idx) + 1 > self.width {
+            self.scroll += 1;
+        }
+    }
+}
+
+/// 单词由字母、数字与下划线组成，路径中的`/`等符号会作为分隔
+#[inline]
+fn is_word(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_'
+}
+
+/// 字符显示的列数，中日韩文字、全角符号与表情符号占两列
+fn char_width(ch: char) -> usize {
+    match ch as u32 {
+        0x1100..=0x115f
+        | 0x2e80..=0x303e
+        | 0x3040..=0xa4cf
+        | 0xac00..=0xd7a3
+        | 0xf900..=0xfaff
+        | 0xfe30..=0xfe4f
+        | 0xff00..=0xff60
+        | 0xffe0..=0xffe6
+        | 0x1f300..=0x1f64f
+        | 0x1f900..=0x1f9ff
+        | 0x20000..=0x3fffd => 2,
+        _ => 1,
+    }
+}
@@ -0,0 +1,102 @@
+/// 输入框的历史
+///
+/// 通过<Up>/<Down>浏览，保存在配置目录下以名字命名的文件中，每行一条，
+/// 最旧的在前；相同的输入只保留最近的一条
+use crate::settings::config_dir;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// 最多保存的条数
+const HISTORY_SIZE: usize = 500;
+
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    /// 保存的文件名
+    name: String,
+    entries: Vec<String>,
+    /// 正在浏览的一条，`None`表示没有在浏览
+    cursor: Option<usize>,
+    /// 开始浏览前的输入，回到末尾时恢复
+    draft: String,
+}
+
+impl History {
+    /// 从配置目录读取已保存的历史
+    pub fn load(name: &str) -> Self {
+        let content = History::path(name).and_then(|path| fs::read_to_string(path).ok());
+        let entries = content
+            .unwrap_or_default()
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(String::from)
+            .collect();
+        History {
+            name: name.to_string(),
+            entries,
+            ..Default::default()
+        }
+    }
+
+    #[inline]
+    fn path(name: &str) -> Option<PathBuf> {
+        Some(config_dir()?.join(name))
+    }
+
+    /// 记录一条输入并写入配置目录
+    pub fn push(&mut self, entry: &str) -> io::Result<()> {
+        self.reset();
+        self.entries.retain(|old| old != entry);
+        self.entries.push(entry.to_string());
+        let overflow = self.entries.len().saturating_sub(HISTORY_SIZE);
+        self.entries.drain(..overflow);
+        self.save()
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let path = History::path(&self.name).ok_or(io::ErrorKind::NotFound)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let content: String = self
+            .entries
+            .iter()
+            .map(|entry| entry.clone() + "\n")
+            .collect();
+        fs::write(path, content)
+    }
+
+    /// 结束浏览
+    #[inline]
+    pub fn reset(&mut self) {
+        self.cursor = None;
+        self.draft.clear();
+    }
+
+    /// 上一条输入，`input`为当前的输入，已经是最旧的一条时返回`None`
+    pub fn prev(&mut self, input: &str) -> Option<&str> {
+        let cursor = match self.cursor {
+            None if self.entries.is_empty() => return None,
+            None => {
+                self.draft = input.to_string();
+                self.entries.len() - 1
+            }
+            Some(0) => return None,
+            Some(cursor) => cursor - 1,
+        };
+        self.cursor = Some(cursor);
+        Some(&self.entries[cursor])
+    }
+
+    /// 下一条输入，越过最新的一条时返回开始浏览前的输入
+    pub fn next(&mut self) -> Option<&str> {
+        let cursor = self.cursor?;
+        if cursor + 1 < self.entries.len() {
+            self.cursor = Some(cursor + 1);
+            Some(&self.entries[cursor + 1])
+        } else {
+            self.cursor = None;
+            Some(&self.draft)
+        }
+    }
+}
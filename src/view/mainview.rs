@@ -2,9 +2,39 @@
 ///
 /// 通过方向键移动光标，键盘输入字符
 ///
+/// - 键入<Enter>换行时自动沿用上一行的缩进，在括号或块起始符后多缩进一级
+/// - 在缩进中键入<Backspace>一次删除一级缩进
+/// - 输入左括号或引号时自动补全右侧字符，在空括号之间退格会同时删除两侧
+/// - 键入<Ctrl+]>跳转到匹配的括号
+///
+/// - 键入<Ctrl+Left>/<Ctrl+Right>按单词移动，<Ctrl+Alt+Left>/<Ctrl+Alt+Right>按子词移动
+/// - 键入<Ctrl+Backspace>/<Ctrl+Delete>按单词删除
+/// - 键入<Delete>删除光标后的字符，键入<Insert>切换插入/覆盖模式
+///
+/// - 按住<Shift>移动光标来选中内容
+/// - 键入<Ctrl+c>/<Ctrl+x>/<Ctrl+v>复制/剪切/粘贴，没有选中内容时复制/剪切整行
+///
+/// - 键入<Ctrl+d>选中光标处的单词，再次键入时在下一个出现位置添加光标
+/// - 键入<Alt+l>在上次查找的所有匹配项处添加光标
+/// - 键入<Ctrl+Alt+Up>/<Ctrl+Alt+Down>在上方/下方添加光标，键入<Alt+c>只保留主光标
+/// - 存在多个光标时，输入、删除与粘贴作用于每个光标，且只算一次编辑
+///
+/// - 键入<Alt+Shift+方向键>或按住<Alt>/<Ctrl>拖动鼠标进行块选择，块的每一行各有一个光标
+/// - 块选择的复制/剪切按矩形进行，粘贴时从光标所在列逐行粘贴，较短的行会用空格补齐
+/// - 单击鼠标移动光标，拖动鼠标选中内容
+///
+/// - 键入<Ctrl+z>/<Ctrl+y>撤销/重做
+/// - 行操作作用于当前行或选中的行：
+///   <Alt+d>复制，<Ctrl+k>删除，<Alt+Up>/<Alt+Down>移动，<Alt+j>与下一行合并，
+///   <Alt+s>/<Alt+S>/<Alt+n>升序/降序/按数字排序，<Alt+u>去重，<Alt+r>反转，<Alt+h>打乱
+///
 /// - 键入<F6>顺序切换当前文件
 /// - 键入<F7>逆序切换当前文件
 /// - 键入<F8>根据输入切换文件
+/// - 键入<Ctrl+p>模糊查找工作区中的文件与打开的文件，按匹配程度与最近使用排序
+/// - 键入<Ctrl+w>关闭当前文件，有未保存的修改时询问是否保存，其余文件的编号不变
+///
+/// - 键入<Ctrl+g>跳转到指定位置，支持`行[:列]`、`+N`/`-N`与`N%`
 ///
 /// - 键入<Ctrl+s>保存当前文件，若没有名字则会有弹窗来输入
 /// - 键入<Ctrl+f>开启查找模式，输入字符串后通过方向键来定位所有匹配项
@@ -12,14 +42,31 @@
 ///
 /// - 键入<Alt+Left>/<Alt+Right>改变主视图大小
 use crate::prelude::*;
+use crate::view::filedialog::{self, FileDialog};
+use crate::view::msgbox::Answer;
 use crate::MsgBox;
 
+use super::picker::Picker;
 use super::SplitNAt;
-use crate::file::Content;
+use crate::file::{Content, EditKind, FileID};
 use getch_rs::Key;
 use std::rc::Rc;
 use widestring::{utf16str, Utf16Str, Utf16String};
 
+mod block;
+mod bracket;
+mod caret;
+mod finder;
+mod indent;
+mod jump;
+mod lines;
+mod word;
+
+use block::Block;
+use caret::Caret;
+use jump::Jump;
+use lines::LineOp;
+
 #[derive(Clone, Debug, Default)]
 enum Mode {
     Search,
@@ -35,6 +82,12 @@ pub struct MainView {
     lnum_clr: Color,
     //line number's stressed color
     lnum_sclr: Color,
+    //matching bracket's color
+    match_clr: Color,
+    //selection's color
+    select_clr: Color,
+    //secondary caret's color
+    caret_clr: Color,
     curr_line: usize,
     curr_idx: usize,
     content: Content,
@@ -43,6 +96,20 @@ pub struct MainView {
     search_stack: Vec<(usize, usize)>,
     search_str: String,
     search_idx: usize,
+    /// 是否为覆盖模式（由<Insert>切换）
+    is_overwrite: bool,
+    /// 选中区域的另一端`(行, 下标)`，光标为选中区域的一端
+    anchor: Option<(usize, usize)>,
+    /// 主光标以外的光标
+    carets: Vec<Caret>,
+    /// 复制的内容，每个光标一项
+    clipboard: Vec<String>,
+    /// 复制的内容是否来自块选择，是则按矩形粘贴
+    is_block_clip: bool,
+    /// 块选择
+    block: Option<Block>,
+    /// 鼠标按下的位置`(行, 显示列)`
+    drag: Option<(usize, usize)>,
 }
 
 impl View for MainView {
@@ -51,11 +118,67 @@ impl View for MainView {
             Mode::Search => {}
             Mode::Normal => {
                 if let Some(msg) = module.recvmsg(&self.name) {
-                    let id = msg.parse::<usize>().unwrap();
-                    module.sendmsg(
-                        String::from("Menu"),
-                        format!("Change to File No.{}", id + 1),
-                    );
+                    if let ["macro", name, target] = msg.split(' ').collect::<Vec<_>>()[..] {
+                        let pos = (self.curr_line, self.curr_idx);
+                        let count = self.run_macro(module, name, target == "matches");
+                        module.file_mod.mut_curr().record(pos, EditKind::Other);
+                        module.sendmsg(
+                            String::from("Menu"),
+                            format!("Run Macro \"{name}\" {count} Times"),
+                        );
+                        return;
+                    }
+                    if let Some(target) = msg.strip_prefix("goto ") {
+                        if !self.goto(target, &module.term, &module.settings) {
+                            module.sendmsg(
+                                String::from("Menu"),
+                                format!("Invalid Position: `{target}`"),
+                            );
+                        }
+                        return;
+                    }
+                    if let Ok(op) = msg.parse::<LineOp>() {
+                        let pos = (self.curr_line, self.curr_idx);
+                        self.line_op_or_warn(module, op);
+                        module.file_mod.mut_curr().record(pos, EditKind::Other);
+                        return;
+                    }
+
+                    if msg == "reload" {
+                        let pos = (self.curr_line, self.curr_idx);
+                        let msg = match module.file_mod.reload() {
+                            Ok(()) => {
+                                self.carets.clear();
+                                self.block = None;
+                                self.clamp();
+                                self.scroll = self.scroll.min(self.curr_line);
+                                module.file_mod.mut_curr().record(pos, EditKind::Other);
+                                format!("File \"{}\" Reloaded", module.file_mod.name())
+                            }
+                            Err(_) => String::from("No File to Reload"),
+                        };
+                        module.sendmsg(String::from("Menu"), msg);
+                        return;
+                    }
+                    if let "close" | "closeothers" | "closeall" = &msg[..] {
+                        self.close(module, &msg);
+                        return;
+                    }
+                    if msg == "saveas" {
+                        self.save_as(module, module.file_mod.curr_id());
+                        return;
+                    }
+                    if msg == "open" {
+                        self.open_file(module);
+                        return;
+                    }
+
+                    // 其余的消息为要切换到的文件编号，无法识别的消息被忽略
+                    let id = match msg.parse::<FileID>() {
+                        Ok(id) if module.file_mod.get(id).is_some() => id,
+                        _ => return,
+                    };
+                    module.sendmsg(String::from("Menu"), format!("Change to File No.{id}"));
 
                     let curr_pos = (self.curr_idx, self.curr_line);
                     let scroll = self.scroll;
@@ -67,10 +190,16 @@ impl View for MainView {
         }
     }
     fn matchar(&mut self, module: &mut Module, key: getch_rs::Key) {
-        match self.mode {
-            Mode::Normal => self.normal_mode(module, key),
-            Mode::Search => self.search_mode(module, key),
-        }
+        let pos = (self.curr_line, self.curr_idx);
+        let kind = match key {
+            Key::Char('\r') => EditKind::Other,
+            Key::Char(_) => EditKind::Insert,
+            Key::Backspace | Key::Delete => EditKind::Delete,
+            _ => EditKind::Other,
+        };
+        self.dispatch(module, key);
+        // 每次编辑后记录撤销历史
+        module.file_mod.mut_curr().record(pos, kind);
     }
 
     fn set_cursor(&self, module: &mut Module) {
@@ -103,15 +232,25 @@ impl View for MainView {
     fn draw(&self, module: &mut Module) -> io::Result<()> {
         let (term, settings) = (&module.term, &mut module.settings);
         self.refresh(term);
-        let content: Vec<Utf16String> = self
-            .content
-            .borrow()
+        let raw = self.content.borrow();
+        // 匹配的括号离开视图太远时不再高亮，避免每次绘制都扫描整个文件
+        let height = (self.end.1.unwrap(term.height) - self.start.1.unwrap(term.height)) as usize;
+        let limit = height + bracket::SCAN_MARGIN;
+        let pair = bracket::find_pair(&raw, self.curr_line, self.curr_idx, limit);
+        let content: Vec<Utf16String> = raw
             .iter()
-            .map(|line| {
-                line.to_string()
-                    .replace("\r", "↵")
-                    .replace("\t", "    ")
-                    .into()
+            .enumerate()
+            .map(|(row, line)| {
+                let mut line = line.to_string().replace("\r", "↵").replace("\t", "    ");
+                // 为行尾的光标留出位置
+                if self
+                    .carets
+                    .iter()
+                    .any(|caret| caret.pos == (row, raw[row].len()))
+                {
+                    line.push(' ');
+                }
+                line.into()
             })
             .collect();
 
@@ -123,8 +262,6 @@ impl View for MainView {
         let is_show_num = settings.is_show_num;
         let line_num_offset = settings.num_offset;
 
-        let mut line_num = self.scroll + 1;
-
         let max_line = width - x_pos - line_num_offset;
         let max_height = (height - y_pos) as usize;
 
@@ -134,9 +271,12 @@ impl View for MainView {
         Cursor::set_csr(x_pos, y_pos);
 
         let mut height_cnt = 1;
-        'out: for line in content.iter().skip(self.scroll) {
+        'out: for (row, line) in content.iter().enumerate().skip(self.scroll) {
+            let line_num = row + 1;
+            let spans = self.spans(&raw, row, pair);
             let mut lines: Vec<Utf16String> = Vec::new();
             for (subline, cnt) in line.splitn_at(max_line as usize) {
+                let offset = cnt as usize * max_line as usize;
                 if is_show_num {
                     let mut number = if cnt == 0 {
                         format!(
@@ -156,15 +296,15 @@ impl View for MainView {
                         let mut highlight = subline.clone();
                         highlight.push_str(&" ".repeat((max_line) as usize - subline.len()));
 
-                        highlight.clr_head(&bclr.lighten(0x6), fclr)
+                        paint(&highlight, offset, &spans, &bclr.lighten(0x6), fclr)
                     } else {
                         number = number.fcolor(lnum_clr).bclr_head(bclr);
-                        subline.clr_head(bclr, fclr)
+                        paint(&subline, offset, &spans, bclr, fclr)
                     };
 
                     lines.push(format!("{number}{subline}").into());
                 } else {
-                    lines.push(subline);
+                    lines.push(paint(&subline, offset, &spans, bclr, fclr).into());
                 }
             }
 
@@ -177,7 +317,6 @@ impl View for MainView {
                 }
                 Cursor::csr_nextline();
             }
-            line_num += 1;
         }
         io::stdout().flush()?;
         Ok(())
@@ -185,7 +324,11 @@ impl View for MainView {
 
     fn init(&mut self, module: &mut Module) {
         let (file_mod, settings) = (&mut module.file_mod, &mut module.settings);
-        self.content = Rc::clone(file_mod.get_content());
+        // 重新初始化时保留当前位置
+        if !Rc::ptr_eq(&self.content, file_mod.get_content()) {
+            self.content = Rc::clone(file_mod.get_content());
+            (self.curr_idx, self.curr_line, self.scroll) = file_mod.curr_status();
+        }
 
         let (bclr, fclr) = (&settings.theme.normal_bclr, &settings.theme.normal_fclr);
         let (lnum_clr, lnum_sclr) = (&settings.theme.weak_fclr, &settings.theme.yellow);
@@ -193,6 +336,9 @@ impl View for MainView {
         self.fcolor = fclr.clone();
         self.lnum_clr = lnum_clr.clone();
         self.lnum_sclr = lnum_sclr.clone();
+        self.match_clr = settings.theme.stress_bclr.clone();
+        self.select_clr = settings.theme.bright_black.clone();
+        self.caret_clr = settings.theme.blue.clone();
     }
 }
 
@@ -203,6 +349,8 @@ impl MainView {
         new_status: (usize, usize, usize),
     ) -> io::Result<()> {
         self.content = Rc::clone(file_mod.get_content());
+        self.anchor = None;
+        self.carets.clear();
         self.curr_idx = new_status.0;
         self.curr_line = new_status.1;
         self.scroll = new_status.2;
@@ -229,6 +377,45 @@ impl MainView {
         (self.end.0.unwrap(term.width) - self.get_text_pos(term, settings).0) as usize
     }
 
+    /// 收集第`row`行需要高亮的区间（显示下标）
+    fn spans(
+        &self,
+        raw: &[Utf16String],
+        row: usize,
+        pair: Option<((usize, usize), (usize, usize))>,
+    ) -> Vec<(usize, usize, &Color)> {
+        let mut spans = Vec::new();
+        let line = &raw[row];
+        for caret in self.all_carets() {
+            if let Some(((sl, si), (el, ei))) = caret.selection() {
+                if (sl..=el).contains(&row) {
+                    let start = if row == sl { si } else { 0 };
+                    let end = if row == el { ei } else { line.len() };
+                    spans.push((
+                        display_idx(line, start),
+                        display_idx(line, end),
+                        &self.select_clr,
+                    ));
+                }
+            }
+        }
+        for caret in &self.carets {
+            if caret.pos.0 == row {
+                let idx = display_idx(line, caret.pos.1);
+                spans.push((idx, idx + 1, &self.caret_clr));
+            }
+        }
+        if let Some((this, other)) = pair {
+            for (line, idx) in [this, other] {
+                if line == row {
+                    let idx = display_idx(&raw[row], idx);
+                    spans.push((idx, idx + 1, &self.match_clr));
+                }
+            }
+        }
+        spans
+    }
+
     #[inline]
     fn pre_all_lines(&self, term: &Term, settings: &Settings) -> usize {
         let (curr_line, idx) = (self.curr_line, self.curr_idx);
@@ -277,13 +464,34 @@ impl MainView {
         self.curr_line -= 1;
     }
 
+    /// 输入字符
+    ///
+    /// 覆盖模式下会替换光标下的字符；
+    /// 开启自动配对时，输入左括号或引号会补全右侧字符，
+    /// 输入的右括号与光标后的字符相同时直接跳过
     #[inline]
-    pub fn push(&mut self, ch: char) {
+    pub fn push(&mut self, ch: char, settings: &Settings) {
         let mut content = self.content.borrow_mut();
         if content.is_empty() {
             content.push(Utf16String::new());
         }
-        content[self.curr_line].insert(self.curr_idx, ch);
+        let line = &mut content[self.curr_line];
+        if self.is_overwrite {
+            if self.curr_idx < line.len() {
+                line.remove(self.curr_idx);
+            }
+        } else if settings.is_auto_pair {
+            if bracket::is_closer(ch) && bracket::char_at(line, self.curr_idx) == Some(ch) {
+                self.curr_idx += 1;
+                return;
+            }
+            if let Some(closer) = bracket::auto_closer(ch) {
+                if bracket::should_pair(line, self.curr_idx, ch) {
+                    line.insert(self.curr_idx, closer);
+                }
+            }
+        }
+        line.insert(self.curr_idx, ch);
         self.curr_idx += 1;
     }
 
@@ -294,20 +502,48 @@ impl MainView {
         self.curr_idx += string.len();
     }
 
+    /// 换行并自动缩进
+    ///
+    /// 新行沿用当前行的缩进，若光标前为左括号或块起始符则多缩进一级，
+    /// 若光标后紧跟对应的右括号，则把右括号放到单独的一行
     #[inline]
-    pub fn push_line(&mut self, term: &Term, settings: &Settings) {
+    pub fn push_line(&mut self, term: &Term, settings: &Settings, extension: Option<&str>) {
         let (line, idx) = (self.curr_line, self.curr_idx);
         let mut content = self.content.borrow_mut();
 
         let curr_line = content[line].clone();
         let (first, last) = curr_line.split_at(idx);
+
+        let leading = indent::leading(first);
+        let mut new_indent = leading.clone();
+        let is_open = indent::opens_block(first, extension);
+        if is_open {
+            new_indent.push_utfstr(&indent::unit(&leading, settings.indent_width));
+        }
+
+        let is_pair = first
+            .to_string()
+            .trim_end()
+            .chars()
+            .last()
+            .and_then(indent::closer)
+            .is_some_and(|closer| last.chars().next() == Some(closer));
+
         content[line] = Utf16String::from(first);
-        content.insert(line + 1, Utf16String::from(last));
+        if is_open && is_pair {
+            let mut closer_line = leading;
+            closer_line.push_utfstr(last);
+            content.insert(line + 1, new_indent.clone());
+            content.insert(line + 2, closer_line);
+        } else {
+            let mut next_line = new_indent.clone();
+            next_line.push_utfstr(last);
+            content.insert(line + 1, next_line);
+        }
         drop(content);
 
-        //self.curr_line += 1;
         self.line_inc(term, settings);
-        self.curr_idx = 0;
+        self.curr_idx = new_indent.len();
     }
 
     #[inline]
@@ -330,8 +566,17 @@ impl MainView {
 
             self.line_dec(term, settings);
         } else {
-            self.content.borrow_mut()[line].remove(idx - 1);
-            self.curr_idx -= 1;
+            let mut content = self.content.borrow_mut();
+            // 在一对空括号之间退格时同时删除两侧
+            if settings.is_auto_pair && bracket::is_empty_pair(&content[line], idx) {
+                content[line].replace_range(idx - 1..idx + 1, utf16str!(""));
+                self.curr_idx -= 1;
+                return;
+            }
+            // 在缩进中退格时一次删除一级缩进
+            let len = indent::dedent_len(&content[line][..idx], settings.indent_width).unwrap_or(1);
+            content[line].replace_range(idx - len..idx, utf16str!(""));
+            self.curr_idx -= len;
         }
     }
 
@@ -378,6 +623,73 @@ impl MainView {
         }
     }
 
+    /// 按单词左移，`is_sub`为真时按子词移动；位于行首时移动到上一行末尾
+    pub fn word_left(&mut self, term: &Term, settings: &Settings, is_sub: bool) {
+        if self.curr_idx == 0 {
+            if self.curr_line > 0 {
+                self.up(term, settings);
+                self.end();
+            }
+            return;
+        }
+        let content = self.content.borrow();
+        self.curr_idx = word::prev(&content[self.curr_line], self.curr_idx, is_sub);
+    }
+
+    /// 按单词右移，`is_sub`为真时按子词移动；位于行尾时移动到下一行开头
+    pub fn word_right(&mut self, term: &Term, settings: &Settings, is_sub: bool) {
+        let content = self.content.borrow();
+        let line = &content[self.curr_line];
+        if self.curr_idx >= line.len() {
+            let is_last = self.curr_line + 1 >= content.len();
+            drop(content);
+            if !is_last {
+                self.down(term, settings);
+                self.home();
+            }
+            return;
+        }
+        self.curr_idx = word::next(line, self.curr_idx, is_sub);
+    }
+
+    /// 删除光标前的一个单词，位于行首时与上一行合并
+    pub fn delete_word(&mut self, term: &Term, settings: &Settings) {
+        if self.curr_idx == 0 {
+            self.delete(term, settings);
+            return;
+        }
+        let mut content = self.content.borrow_mut();
+        let line = &mut content[self.curr_line];
+        let start = word::prev(line, self.curr_idx, false);
+        line.replace_range(start..self.curr_idx, utf16str!(""));
+        self.curr_idx = start;
+    }
+
+    /// 删除光标后的一个单词，位于行尾时与下一行合并
+    pub fn delete_word_forward(&mut self) {
+        let (line, idx) = (self.curr_line, self.curr_idx);
+        let mut content = self.content.borrow_mut();
+        if idx >= content[line].len() {
+            drop(content);
+            self.delete_forward();
+            return;
+        }
+        let end = word::next(&content[line], idx, false);
+        content[line].replace_range(idx..end, utf16str!(""));
+    }
+
+    /// 删除光标后的字符，位于行尾时与下一行合并
+    pub fn delete_forward(&mut self) {
+        let (line, idx) = (self.curr_line, self.curr_idx);
+        let mut content = self.content.borrow_mut();
+        if idx < content[line].len() {
+            content[line].remove(idx);
+        } else if line + 1 < content.len() {
+            let next = content.remove(line + 1);
+            content[line].push_utfstr(&next);
+        }
+    }
+
     #[inline]
     pub fn end(&mut self) {
         let idx = self.content.borrow()[self.curr_line].len();
@@ -389,22 +701,788 @@ impl MainView {
         self.curr_idx = 0;
     }
 
+    /// 跳转到与光标处括号匹配的括号
+    pub fn jump_pair(&mut self, term: &Term, settings: &Settings) {
+        let content = self.content.borrow();
+        let pair = bracket::find_pair(&content, self.curr_line, self.curr_idx, usize::MAX);
+        drop(content);
+        if let Some((_, (line, idx))) = pair {
+            self.set_pos((line, idx));
+            self.follow(term, settings);
+        }
+    }
+
+    /// 若光标所在行不在视图内，则滚动使其居中
+    pub fn follow(&mut self, term: &Term, settings: &Settings) {
+        let height = (self.end.1.unwrap(term.height) - self.start.1.unwrap(term.height)) as usize;
+        if self.curr_line >= self.scroll && self.pre_all_lines(term, settings) + 4 <= height {
+            return;
+        }
+        self.center(term, settings);
+    }
+
+    /// 滚动视图使光标所在行居中
+    pub fn center(&mut self, term: &Term, settings: &Settings) {
+        let height = (self.end.1.unwrap(term.height) - self.start.1.unwrap(term.height)) as usize;
+        self.scroll = self.curr_line;
+        while self.scroll > 0 && self.pre_all_lines(term, settings) < height / 2 {
+            self.scroll -= 1;
+        }
+    }
+
+    /// 跳转到`target`描述的位置并使其居中，`target`无效时返回`false`
+    ///
+    /// `target`的形式见`Jump`
+    pub fn goto(&mut self, target: &str, term: &Term, settings: &Settings) -> bool {
+        let jump = match target.parse::<Jump>() {
+            Ok(jump) => jump,
+            Err(_) => return false,
+        };
+        let total = self.content.borrow().len();
+        let (line, col) = jump.resolve(self.curr_line, total);
+
+        self.anchor = None;
+        self.block = None;
+        self.set_pos((line, col.unwrap_or(0)));
+        self.clamp();
+        self.center(term, settings);
+        true
+    }
+
+    /// 模糊查找文件并切换到选中的文件
+    pub fn find_file(&mut self, module: &mut Module) {
+        let items = finder::buffers(&module.file_mod);
+        let source = finder::walk(&module.file_mod);
+        let item = Picker::new()
+            .title("Find File")
+            .items(items)
+            .source(source)
+            .default_pos(module)
+            .wait(module);
+
+        if let Some(item) = item {
+            let file_mod = &mut module.file_mod;
+            let path = finder::path(file_mod, &item.label);
+            let id = match file_mod.open(&path) {
+                Ok(id) => id,
+                Err(err) => return open_error(module, err),
+            };
+            let curr_pos = (self.curr_idx, self.curr_line);
+            let new_status = file_mod.shift_to(id, curr_pos, self.scroll);
+            self.sync(file_mod, new_status).unwrap();
+            module.sendmsg(String::from("Menu"), format!("Change to File No.{id}"));
+        }
+    }
+
+    /// 关闭文件，`which`为`close`、`closeothers`或`closeall`
+    ///
+    /// 遇到取消时停止关闭剩下的文件
+    pub fn close(&mut self, module: &mut Module, which: &str) {
+        let file_mod = &mut module.file_mod;
+        file_mod.save_status((self.curr_idx, self.curr_line), self.scroll);
+        let curr_id = file_mod.curr_id();
+        let ids: Vec<FileID> = match which {
+            "close" => vec![curr_id],
+            "closeothers" => file_mod
+                .ids()
+                .into_iter()
+                .filter(|&id| id != curr_id)
+                .collect(),
+            _ => file_mod.ids(),
+        };
+
+        let mut count = 0;
+        for id in ids {
+            if !self.close_file(module, id) {
+                module.sendmsg(String::from("Menu"), String::from("Close Cancelled"));
+                break;
+            }
+            count += 1;
+        }
+        if count > 0 {
+            module.sendmsg(String::from("Menu"), format!("Closed {count} File(s)"));
+        }
+
+        let new_status = module.file_mod.curr_status();
+        self.sync(&mut module.file_mod, new_status).unwrap();
+    }
+
+    /// 保存编号为`id`的文件，给出`name`时另存为，失败时弹窗显示原因，返回是否已保存
+    fn save(&mut self, module: &mut Module, id: FileID, name: Option<String>) -> bool {
+        match module.file_mod.save_file(id, name) {
+            Ok(()) => {
+                let name = module.file_mod.get(id).map_or("", |file| file.name());
+                let msg = format!("File \"{name}\" Saved");
+                module.sendmsg(String::from("Menu"), msg);
+                true
+            }
+            Err(err) => {
+                MsgBox::new()
+                    .title("Error")
+                    .default_pos(module)
+                    .show(module, &format!("Can't Save: {err}"));
+                false
+            }
+        }
+    }
+
+    /// 在文件选择框中选择路径，另存编号为`id`的文件，返回是否已保存
+    pub fn save_as(&mut self, module: &mut Module, id: FileID) -> bool {
+        let file = module.file_mod.get(id);
+        let name = file.map(|file| file.name().to_string()).unwrap_or_default();
+        // 已有路径的文件从其所在目录开始选择，新文件从当前目录开始
+        let base = file
+            .and_then(|file| file.pathbuf().parent())
+            .filter(|dir| dir.is_dir())
+            .unwrap_or(module.file_mod.curr_dir())
+            .to_path_buf();
+        let path = FileDialog::new()
+            .title("Save As")
+            .mode(filedialog::Mode::Save)
+            .base(&base)
+            .input(&name)
+            .default_pos(module)
+            .wait(module);
+        match path {
+            Some(path) => self.save(module, id, Some(path.to_string_lossy().into_owned())),
+            None => false,
+        }
+    }
+
+    /// 在文件选择框中选择文件并打开
+    pub fn open_file(&mut self, module: &mut Module) {
+        let path = FileDialog::new()
+            .title("Open")
+            .mode(filedialog::Mode::Open)
+            .base(module.file_mod.curr_dir())
+            .default_pos(module)
+            .wait(module);
+        if let Some(path) = path {
+            let file_mod = &mut module.file_mod;
+            let id = match file_mod.open(&path) {
+                Ok(id) => id,
+                Err(err) => return open_error(module, err),
+            };
+            let curr_pos = (self.curr_idx, self.curr_line);
+            let new_status = file_mod.shift_to(id, curr_pos, self.scroll);
+            self.sync(file_mod, new_status).unwrap();
+            module.sendmsg(String::from("Menu"), format!("Change to File No.{id}"));
+        }
+    }
+
+    /// 关闭编号为`id`的文件，有未保存的修改时询问是否保存，返回是否已关闭
+    fn close_file(&mut self, module: &mut Module, id: FileID) -> bool {
+        let (is_dirty, name) = match module.file_mod.get(id) {
+            Some(file) => (file.is_dirty(), file.name().to_string()),
+            None => return true,
+        };
+        if is_dirty {
+            let title = if name.is_empty() {
+                String::from("Save \"[No Name]\"?")
+            } else {
+                format!("Save \"{name}\"?")
+            };
+            let answer = MsgBox::new()
+                .title(&title)
+                .default_pos(module)
+                .confirm(module);
+            match answer {
+                Answer::Yes => {
+                    let is_saved = if name.is_empty() {
+                        self.save_as(module, id)
+                    } else {
+                        self.save(module, id, None)
+                    };
+                    if !is_saved {
+                        return false;
+                    }
+                }
+                Answer::No => (),
+                Answer::Cancel => return false,
+            }
+        }
+        module.file_mod.close(id);
+        true
+    }
+
+    /// 执行移动光标的按键，返回按键是否为移动操作
+    ///
+    /// `mods`中不含Shift，是否选中由调用者处理
+    fn motion(&mut self, key: &Key, mods: Mods, term: &Term, settings: &Settings) -> bool {
+        match (key, mods) {
+            (Key::Up, Mods::NONE) => self.up(term, settings),
+            (Key::Down, Mods::NONE) => self.down(term, settings),
+            (Key::Left, Mods::NONE) => self.left(),
+            (Key::Right, Mods::NONE) => self.right(),
+            (Key::Left, Mods::CTRL) => self.word_left(term, settings, false),
+            (Key::Right, Mods::CTRL) => self.word_right(term, settings, false),
+            (Key::Left, Mods::CTRL_ALT) => self.word_left(term, settings, true),
+            (Key::Right, Mods::CTRL_ALT) => self.word_right(term, settings, true),
+            (Key::Home, Mods::NONE) => self.home(),
+            (Key::End, Mods::NONE) => self.end(),
+            (Key::PageUp, Mods::NONE) => {
+                for _ in [0; 25] {
+                    self.up(term, settings);
+                }
+            }
+            (Key::PageDown, Mods::NONE) => {
+                for _ in [0; 25] {
+                    self.down(term, settings);
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// 选中区域的起点与终点，按位置排序
+    fn selection(&self) -> Option<((usize, usize), (usize, usize))> {
+        let anchor = self.anchor?;
+        let caret = (self.curr_line, self.curr_idx);
+        if anchor == caret {
+            return None;
+        }
+        Some((anchor.min(caret), anchor.max(caret)))
+    }
+
+    /// 删除选中的内容并取消选中，返回是否有内容被删除
+    fn delete_selection(&mut self, term: &Term, settings: &Settings) -> bool {
+        let selection = self.selection();
+        self.anchor = None;
+        let ((sl, si), (el, ei)) = match selection {
+            Some(selection) => selection,
+            None => return false,
+        };
+
+        let mut content = self.content.borrow_mut();
+        let tail = content[el][ei..].to_owned();
+        content[sl].truncate(si);
+        content[sl].push_utfstr(&tail);
+        content.drain(sl + 1..=el);
+        drop(content);
+
+        self.set_pos((sl, si));
+        self.follow(term, settings);
+        true
+    }
+
+    /// 当前行或选中区域覆盖的行`[start, end]`
+    ///
+    /// 选中区域结束于行首时不包含该行
+    fn line_range(&self) -> (usize, usize) {
+        match self.selection() {
+            Some(((sl, _), (el, 0))) if el > sl => (sl, el - 1),
+            Some(((sl, _), (el, _))) => (sl, el),
+            None => (self.curr_line, self.curr_line),
+        }
+    }
+
+    /// 对当前行或选中的行执行行操作，存在多个光标时只作用于主光标
+    ///
+    /// 排序、去重、反转与打乱需要选中内容，没有时不执行并返回`false`
+    pub fn line_op(&mut self, op: LineOp, term: &Term, settings: &Settings) -> bool {
+        if self.selection().is_none() && op.needs_selection() {
+            return false;
+        }
+        self.carets.clear();
+        let (start, end) = self.line_range();
+        let mut content = self.content.borrow_mut();
+
+        // 光标的移动需要在释放内容的借用后进行
+        let mut shift = None;
+        match op {
+            LineOp::Duplicate => {
+                lines::duplicate(&mut content, start, end);
+                shift = Some((end - start + 1, true));
+            }
+            LineOp::Delete => {
+                lines::delete(&mut content, start, end);
+                self.anchor = None;
+                self.curr_line = start.min(content.len() - 1);
+            }
+            LineOp::MoveUp => {
+                if lines::move_up(&mut content, start, end) {
+                    shift = Some((1, false));
+                }
+            }
+            LineOp::MoveDown => {
+                if lines::move_down(&mut content, start, end) {
+                    shift = Some((1, true));
+                }
+            }
+            LineOp::Join => {
+                if let Some(idx) = lines::join(&mut content, start, end) {
+                    self.anchor = None;
+                    (self.curr_line, self.curr_idx) = (start, idx);
+                }
+            }
+            _ => lines::reorder(&mut content, start, end, op),
+        }
+        drop(content);
+
+        if let Some((delta, is_down)) = shift {
+            self.shift_lines(delta, is_down);
+        }
+        self.clamp();
+        self.follow(term, settings);
+        true
+    }
+
+    /// 执行行操作，因没有选中内容而未执行时提示
+    fn line_op_or_warn(&mut self, module: &mut Module, op: LineOp) {
+        if !self.line_op(op, &module.term, &module.settings) {
+            module.sendmsg(String::from("Menu"), String::from("No Selection"));
+        }
+    }
+
+    /// 把光标与选中区域整体上下移动`delta`行
+    #[inline]
+    fn shift_lines(&mut self, delta: usize, is_down: bool) {
+        let shift = |line: usize| if is_down { line + delta } else { line - delta };
+        self.curr_line = shift(self.curr_line);
+        if let Some((line, idx)) = self.anchor {
+            self.anchor = Some((shift(line), idx));
+        }
+    }
+
+    /// 把光标与选中区域限制在内容范围内
+    fn clamp(&mut self) {
+        let content = self.content.borrow();
+        let clamp = |(line, idx): (usize, usize)| {
+            let line = line.min(content.len() - 1);
+            (line, idx.min(content[line].len()))
+        };
+        (self.curr_line, self.curr_idx) = clamp((self.curr_line, self.curr_idx));
+        self.anchor = self.anchor.map(clamp);
+    }
+
+    /// 撤销/重做后恢复光标位置
+    fn restore(&mut self, pos: Option<(usize, usize)>, term: &Term, settings: &Settings) {
+        if let Some(pos) = pos {
+            self.anchor = None;
+            self.set_pos(pos);
+            self.clamp();
+            self.follow(term, settings);
+        }
+    }
+
+    /// 按当前模式处理按键，不记录撤销历史
+    fn dispatch(&mut self, module: &mut Module, key: getch_rs::Key) {
+        match self.mode {
+            Mode::Normal => self.normal_mode(module, key),
+            Mode::Search => self.search_mode(module, key),
+        }
+    }
+
+    /// 在选中的每一行行首或上次查找的每个匹配项处回放宏，返回回放的次数
+    ///
+    /// 从后往前回放，使宏对行数的改动不影响之前的位置
+    fn run_macro(&mut self, module: &mut Module, name: &str, is_matches: bool) -> usize {
+        let keys = match module.macros.get(name) {
+            Some(keys) => keys.clone(),
+            None => return 0,
+        };
+        let targets: Vec<(usize, usize)> = if is_matches {
+            let needle = Utf16String::from(self.search_str.as_str());
+            caret::find_all(&self.content.borrow(), &needle)
+        } else {
+            let (start, end) = self.line_range();
+            (start..=end).map(|line| (line, 0)).collect()
+        };
+
+        self.block = None;
+        for pos in targets.iter().rev() {
+            self.anchor = None;
+            self.set_pos(*pos);
+            self.clamp();
+            module.macros.queue(&keys);
+            while let Some(key) = module.macros.pop() {
+                self.dispatch(module, key);
+            }
+        }
+        targets.len()
+    }
+
+    /// 移动主光标，并取消其他光标
     #[inline]
     pub fn set_pos(&mut self, pos: (usize, usize)) {
         self.curr_line = pos.0;
         self.curr_idx = pos.1;
+        self.carets.clear();
+    }
+
+    /// 包括主光标在内的所有光标，按位置排序
+    fn all_carets(&self) -> Vec<Caret> {
+        let mut carets = self.carets.clone();
+        carets.push(Caret::new((self.curr_line, self.curr_idx), self.anchor));
+        carets.sort_by_key(Caret::start);
+        carets
+    }
+
+    /// 在每个光标处执行`edit`，其参数为光标按位置排序后的序号
+    ///
+    /// 光标从后往前依次处理，结束后合并重合的光标并使主光标可见
+    fn each_caret(
+        &mut self,
+        term: &Term,
+        settings: &Settings,
+        mut edit: impl FnMut(&mut Self, usize),
+    ) {
+        if self.carets.is_empty() {
+            edit(self, 0);
+            return;
+        }
+        let primary = Caret::new((self.curr_line, self.curr_idx), self.anchor);
+        let carets = self.all_carets();
+        let primary_idx = carets.iter().position(|caret| *caret == primary);
+        self.carets.clear();
+        let scroll = self.scroll;
+
+        let mut done = Vec::with_capacity(carets.len());
+        for (i, caret) in carets.into_iter().enumerate().rev() {
+            (self.curr_line, self.curr_idx) = caret.pos;
+            self.anchor = caret.anchor;
+            edit(self, i);
+            let content = self.content.borrow();
+            let pos = caret::to_end(&content, (self.curr_line, self.curr_idx));
+            let anchor = self.anchor.map(|anchor| caret::to_end(&content, anchor));
+            done.push((i, pos, anchor));
+        }
+
+        let content = self.content.borrow();
+        let mut carets = Vec::with_capacity(done.len());
+        for (i, pos, anchor) in done.into_iter().rev() {
+            let pos = caret::from_end(&content, pos);
+            let anchor = anchor.map(|anchor| caret::from_end(&content, anchor));
+            if Some(i) == primary_idx {
+                (self.curr_line, self.curr_idx) = pos;
+                self.anchor = anchor;
+            } else {
+                carets.push(Caret::new(pos, anchor));
+            }
+        }
+        drop(content);
+
+        let primary = (self.curr_line, self.curr_idx);
+        carets.dedup_by_key(|caret| caret.pos);
+        carets.retain(|caret| caret.pos != primary);
+        self.carets = carets;
+        self.scroll = scroll;
+        self.follow(term, settings);
+    }
+
+    /// 选中光标处的单词；已有选中内容时，在其下一个出现位置添加光标并选中
+    fn select_next(&mut self, term: &Term, settings: &Settings) {
+        let content = self.content.borrow();
+        let (start, end) = match self.selection() {
+            Some(selection) => selection,
+            None => {
+                let line = self.curr_line;
+                if let Some((start, end)) = word::around(&content[line], self.curr_idx) {
+                    self.anchor = Some((line, start));
+                    self.curr_idx = end;
+                }
+                return;
+            }
+        };
+        // 只支持单行的选中内容
+        if start.0 != end.0 {
+            return;
+        }
+
+        let needle = content[start.0][start.1..end.1].to_owned();
+        let found = caret::find_all(&content, &needle);
+        let starts: Vec<(usize, usize)> = self.all_carets().iter().map(Caret::start).collect();
+        let last = starts.iter().max().copied().unwrap_or_default();
+        let next = found
+            .iter()
+            .filter(|pos| **pos > last)
+            .chain(found.iter())
+            .find(|pos| !starts.contains(pos));
+        let (line, idx) = match next {
+            Some(pos) => *pos,
+            None => return,
+        };
+        drop(content);
+
+        let primary = Caret::new((self.curr_line, self.curr_idx), self.anchor);
+        self.carets.push(primary);
+        self.anchor = Some((line, idx));
+        (self.curr_line, self.curr_idx) = (line, idx + needle.len());
+        self.follow(term, settings);
+    }
+
+    /// 在上次查找的所有匹配项处添加光标并选中，返回匹配项的数量
+    fn select_matches(&mut self, term: &Term, settings: &Settings) -> usize {
+        let needle = Utf16String::from(self.search_str.as_str());
+        let found = caret::find_all(&self.content.borrow(), &needle);
+        if found.is_empty() {
+            return 0;
+        }
+
+        let curr = (self.curr_line, self.curr_idx);
+        let primary = found.iter().position(|pos| *pos >= curr).unwrap_or(0);
+        self.carets = found
+            .iter()
+            .map(|&(line, idx)| Caret::new((line, idx + needle.len()), Some((line, idx))))
+            .collect();
+        let primary = self.carets.remove(primary);
+        (self.curr_line, self.curr_idx) = primary.pos;
+        self.anchor = primary.anchor;
+        self.follow(term, settings);
+        found.len()
+    }
+
+    /// 在最上方光标的上一行或最下方光标的下一行添加光标
+    fn add_caret(&mut self, is_down: bool, term: &Term, settings: &Settings) {
+        let carets = self.all_carets();
+        let edge = carets.iter().map(|caret| caret.pos);
+        let (line, idx) = if is_down { edge.max() } else { edge.min() }.unwrap_or_default();
+
+        let content = self.content.borrow();
+        let line = match is_down {
+            true if line + 1 < content.len() => line + 1,
+            false if line > 0 => line - 1,
+            _ => return,
+        };
+        let idx = idx.min(content[line].len());
+        drop(content);
+
+        let primary = Caret::new((self.curr_line, self.curr_idx), self.anchor);
+        self.carets.push(primary);
+        self.anchor = None;
+        (self.curr_line, self.curr_idx) = (line, idx);
+        self.follow(term, settings);
+    }
+
+    /// 复制每个光标选中的内容，没有选中内容时复制整行
+    fn copy(&mut self) {
+        self.is_block_clip = false;
+        let carets = self.all_carets();
+        let content = self.content.borrow();
+        self.clipboard = carets
+            .iter()
+            .map(|caret| match caret.selection() {
+                Some((start, end)) => caret::text(&content, start, end),
+                None => format!("{}\n", content[caret.pos.0]),
+            })
+            .collect();
+    }
+
+    /// 粘贴复制的内容
+    ///
+    /// 复制时的光标数与当前光标数相同时，每个光标粘贴对应的一项，否则粘贴全部内容
+    fn paste(&mut self, term: &Term, settings: &Settings) {
+        if self.is_block_clip && self.carets.is_empty() {
+            self.delete_selection(term, settings);
+            self.paste_block(term, settings);
+            return;
+        }
+
+        let clipboard = std::mem::take(&mut self.clipboard);
+        let mut joined = String::new();
+        for piece in &clipboard {
+            if !joined.is_empty() && !joined.ends_with('\n') {
+                joined.push('\n');
+            }
+            joined.push_str(piece);
+        }
+
+        let is_spread = clipboard.len() == self.carets.len() + 1;
+        self.each_caret(term, settings, |view, i| {
+            view.delete_selection(term, settings);
+            view.insert_text(if is_spread { &clipboard[i] } else { &joined });
+        });
+        self.clipboard = clipboard;
+        self.follow(term, settings);
+    }
+
+    /// 在光标处插入可能包含换行的文本
+    fn insert_text(&mut self, text: &str) {
+        let (line, idx) = (self.curr_line, self.curr_idx);
+        let mut content = self.content.borrow_mut();
+        let tail = content[line][idx..].to_owned();
+        content[line].truncate(idx);
+
+        let mut parts = text.split('\n');
+        content[line].push_str(parts.next().unwrap_or_default());
+        let rest: Vec<Utf16String> = parts.map(Utf16String::from).collect();
+        let last = line + rest.len();
+        content.splice(line + 1..line + 1, rest);
+
+        self.curr_idx = content[last].len();
+        self.curr_line = last;
+        content[last].push_utfstr(&tail);
+    }
+
+    /// 按<Alt+Shift+方向键>移动块选择的一端，尚未开始块选择时从光标处开始
+    fn move_block(&mut self, key: &Key, term: &Term, settings: &Settings) {
+        let content = self.content.borrow();
+        let line = self.curr_line;
+        let cursor = (line, display_idx(&content[line], self.curr_idx));
+        let mut block = self.block.unwrap_or(Block::new(cursor, cursor));
+        let (line, col) = &mut block.cursor;
+        match key {
+            Key::Up => *line = line.saturating_sub(1),
+            Key::Down => *line = (*line + 1).min(content.len() - 1),
+            Key::Left => *col = col.saturating_sub(1),
+            Key::Right => *col += 1,
+            _ => (),
+        }
+        drop(content);
+        self.select_block(block, term, settings);
+    }
+
+    /// 选中块，并在块的每一行放置一个光标
+    fn select_block(&mut self, block: Block, term: &Term, settings: &Settings) {
+        let (top, bottom) = block.rows();
+        let (left, right) = block.cols();
+        let is_left = block.cursor.1 < block.anchor.1;
+
+        let content = self.content.borrow();
+        let mut carets: Vec<Caret> = (top..=bottom)
+            .map(|row| {
+                let line = &content[row];
+                let start = (row, block::unit_idx(line, left));
+                let end = (row, block::unit_idx(line, right));
+                if is_left {
+                    Caret::new(start, Some(end))
+                } else {
+                    Caret::new(end, Some(start))
+                }
+            })
+            .collect();
+        drop(content);
+
+        let primary = carets.remove(block.cursor.0 - top);
+        (self.curr_line, self.curr_idx) = primary.pos;
+        self.anchor = primary.anchor;
+        self.carets = carets;
+        self.block = Some(block);
+        self.follow(term, settings);
+    }
+
+    /// 用空格把块中较短的行补齐到块的左边界，使每一行的光标位于同一列
+    fn pad_block(&mut self, block: Block, term: &Term, settings: &Settings) {
+        let (top, bottom) = block.rows();
+        let mut content = self.content.borrow_mut();
+        for line in &mut content[top..=bottom] {
+            block::pad(line, block.cols().0);
+        }
+        drop(content);
+        self.select_block(block, term, settings);
+        self.block = None;
+    }
+
+    /// 复制块中的内容，每行一项，不足块宽度的部分用空格补齐
+    fn copy_block(&mut self, block: Block) {
+        let (top, bottom) = block.rows();
+        let (left, right) = block.cols();
+        let content = self.content.borrow();
+        self.clipboard = content[top..=bottom]
+            .iter()
+            .map(|line| block::slice(line, left, right))
+            .collect();
+        self.is_block_clip = true;
+    }
+
+    /// 按矩形粘贴：从光标所在列开始每项粘贴到一行，超出文末时添加新行
+    fn paste_block(&mut self, term: &Term, settings: &Settings) {
+        let (line, idx) = (self.curr_line, self.curr_idx);
+        let mut content = self.content.borrow_mut();
+        let col = display_idx(&content[line], idx);
+        let mut end = idx;
+        for (k, piece) in self.clipboard.iter().enumerate() {
+            let row = line + k;
+            if row >= content.len() {
+                content.push(Utf16String::new());
+            }
+            block::pad(&mut content[row], col);
+            let at = block::unit_idx(&content[row], col);
+            let piece = Utf16String::from(piece.as_str());
+            content[row].insert_utfstr(at, &piece);
+            if k == 0 {
+                end = at + piece.len();
+            }
+        }
+        drop(content);
+
+        self.curr_idx = end;
+        self.follow(term, settings);
+    }
+
+    /// 屏幕坐标对应的`(行, 显示列)`，位于文本区域之外时返回`None`
+    fn pos_at(&self, x: u16, y: u16, term: &Term, settings: &Settings) -> Option<(usize, usize)> {
+        let (text_x, text_y) = self.get_text_pos(term, settings);
+        if x < text_x || y < text_y || y >= self.end.1.unwrap(term.height) {
+            return None;
+        }
+        let max = self.get_vpos_max(term, settings);
+        let (x, mut y) = ((x - text_x) as usize, (y - text_y) as usize);
+
+        let content = self.content.borrow();
+        for (row, line) in content.iter().enumerate().skip(self.scroll) {
+            let rows = display_idx(line, line.len()).div_ceil(max).max(1);
+            if y < rows {
+                return Some((row, y * max + x));
+            }
+            y -= rows;
+        }
+        let last = content.len() - 1;
+        Some((last, display_idx(&content[last], content[last].len())))
+    }
+
+    /// 鼠标左键：单击移动光标，拖动选中内容，按住<Alt>或<Ctrl>拖动时为块选择
+    fn click(&mut self, mouse: key::Mouse, term: &Term, settings: &Settings) {
+        if mouse.kind == key::MouseKind::Release {
+            self.drag = None;
+            return;
+        }
+        let (line, col) = match self.pos_at(mouse.x, mouse.y, term, settings) {
+            Some(pos) => pos,
+            None => return,
+        };
+        let idx = block::unit_idx(&self.content.borrow()[line], col);
+
+        match (mouse.kind, self.drag) {
+            (key::MouseKind::Press, _) => {
+                self.block = None;
+                self.anchor = None;
+                self.set_pos((line, idx));
+                self.drag = Some((line, col));
+            }
+            (key::MouseKind::Drag, Some(start)) if mouse.mods.alt() || mouse.mods.ctrl() => {
+                self.select_block(Block::new(start, (line, col)), term, settings);
+            }
+            (key::MouseKind::Drag, Some((start_line, start_col))) => {
+                let start_idx = block::unit_idx(&self.content.borrow()[start_line], start_col);
+                self.block = None;
+                self.set_pos((line, idx));
+                self.anchor = Some((start_line, start_idx));
+            }
+            _ => (),
+        }
+    }
+
+    /// 剪切光标所在的行
+    fn cut_line(&mut self) {
+        let line = self.curr_line;
+        let mut content = self.content.borrow_mut();
+        lines::delete(&mut content, line, line);
+        self.curr_line = line.min(content.len() - 1);
+        self.curr_idx = 0;
     }
 
     pub fn search_mode(&mut self, module: &mut Module, key: getch_rs::Key) {
         let term = &module.term;
         match key {
             Key::Ctrl('f') => {
+                // 取消时不替换，空的输入表示删除匹配项
                 let ret = MsgBox::new()
                     .title("Replace")
                     .default_pos(module)
-                    .wait::<String>(module)
-                    .unwrap_or_default();
-                if !ret.is_empty() {
+                    .ask::<String>(module);
+                if let Some(Ok(ret)) = ret {
                     let mut content = self.content.borrow_mut();
                     let start = self.curr_idx;
                     let end = start + self.search_str.len();
@@ -469,23 +1547,19 @@ impl MainView {
                 self.set_pos(self.search_stack[len - 1]);
             }
 
-            Key::Other(key) => {
-                match key[..] {
-                    // Alt(Left)
-                    [27, 91, 49, 59, 51, 68] => {
-                        self.resize(term, -1, 0, 0, 0);
-                        module.push_op(Op::Resize(String::from("FileTree"), (0, 0, -1, 0)));
-                        module.push_op(Op::Resize(String::from("TopBar"), (-1, 0, 0, 0)));
-                    }
-                    // Alt(Right)
-                    [27, 91, 49, 59, 51, 67] => {
-                        self.resize(term, 1, 0, 0, 0);
-                        module.push_op(Op::Resize(String::from("FileTree"), (0, 0, 1, 0)));
-                        module.push_op(Op::Resize(String::from("TopBar"), (1, 0, 0, 0)));
-                    }
-                    _ => (),
-                };
-            }
+            key @ Key::Other(_) => match key::decode(&key) {
+                Some((Key::Left, Mods::ALT)) => {
+                    self.resize(term, -1, 0, 0, 0);
+                    module.push_op(Op::Resize(String::from("FileTree"), (0, 0, -1, 0)));
+                    module.push_op(Op::Resize(String::from("TopBar"), (-1, 0, 0, 0)));
+                }
+                Some((Key::Right, Mods::ALT)) => {
+                    self.resize(term, 1, 0, 0, 0);
+                    module.push_op(Op::Resize(String::from("FileTree"), (0, 0, 1, 0)));
+                    module.push_op(Op::Resize(String::from("TopBar"), (1, 0, 0, 0)));
+                }
+                _ => (),
+            },
 
             _ => (),
         }
@@ -500,114 +1574,223 @@ impl MainView {
 
     pub fn normal_mode(&mut self, module: &mut Module, key: getch_rs::Key) {
         let (term, file_mod, settings) = (&module.term, &mut module.file_mod, &mut module.settings);
+
+        if let Some(mouse) = key::mouse(&key) {
+            self.click(mouse, term, settings);
+            return;
+        }
+
+        // 块选择：复制时保留，输入或粘贴前补齐较短的行，其他按键取消块选择
+        let (base, mods) = key::decode(&key).unwrap_or((key.clone(), Mods::NONE));
+        let is_arrow = matches!(base, Key::Up | Key::Down | Key::Left | Key::Right);
+        if mods == Mods::ALT_SHIFT && is_arrow {
+            self.move_block(&base, term, settings);
+            return;
+        }
+        if let Some(block) = self.block.take() {
+            match key {
+                Key::Ctrl('c') => {
+                    self.copy_block(block);
+                    self.block = Some(block);
+                    return;
+                }
+                Key::Ctrl('x') => {
+                    self.copy_block(block);
+                    self.each_caret(term, settings, |view, _| {
+                        view.delete_selection(term, settings);
+                    });
+                    return;
+                }
+                Key::Char(_) | Key::Ctrl('v') => self.pad_block(block, term, settings),
+                _ => (),
+            }
+        }
+
+        // 移动所有光标，按住Shift时扩展选中区域，否则取消选中
+        let mut is_motion = false;
+        self.each_caret(term, settings, |view, _| {
+            let pos = (view.curr_line, view.curr_idx);
+            is_motion = view.motion(&base, mods.without_shift(), term, settings);
+            if is_motion {
+                view.anchor = if mods.shift() {
+                    view.anchor.or(Some(pos))
+                } else {
+                    None
+                };
+            }
+        });
+        if is_motion {
+            return;
+        }
+
         match key {
             Key::Ctrl('f') => {
                 let ret = MsgBox::new()
                     .title("Search")
                     .default_pos(module)
-                    .wait::<String>(module)
-                    .unwrap_or_default();
-                if !ret.is_empty() {
-                    self.search_str = ret.clone();
-
-                    let content = self.content.borrow();
-                    let mut lines: usize = 0;
-                    let match_str: Vec<_> = content
-                        .iter()
-                        .flat_map(move |line| {
-                            lines += 1;
-                            line.to_string()
-                                .match_indices(&ret)
-                                .map(|pat| (lines - 1, pat.0))
-                                .collect::<Vec<(usize, usize)>>()
-                        })
-                        .collect();
-                    drop(content);
-
-                    if !match_str.is_empty() {
-                        self.search_stack = match_str;
-                        self.mode = Mode::Search;
-                        self.set_pos(self.search_stack[self.search_idx]);
-                        module.sendmsg(
-                            String::from("Menu"),
-                            format!(
-                                "Search for String \"{}\" at Index {}",
-                                self.search_str, self.search_idx
-                            ),
-                        );
-                    } else {
-                        module.sendmsg(
-                            String::from("Menu"),
-                            format!("Can't Find String \"{}\"", self.search_str),
-                        );
-                    }
+                    .ask::<String>(module);
+                let ret = match ret {
+                    Some(Ok(ret)) if !ret.is_empty() => ret,
+                    _ => return,
+                };
+                self.search_str = ret.clone();
+
+                let content = self.content.borrow();
+                let mut lines: usize = 0;
+                let match_str: Vec<_> = content
+                    .iter()
+                    .flat_map(move |line| {
+                        lines += 1;
+                        line.to_string()
+                            .match_indices(&ret)
+                            .map(|pat| (lines - 1, pat.0))
+                            .collect::<Vec<(usize, usize)>>()
+                    })
+                    .collect();
+                drop(content);
+
+                if !match_str.is_empty() {
+                    self.search_stack = match_str;
+                    self.mode = Mode::Search;
+                    self.set_pos(self.search_stack[self.search_idx]);
+                    module.sendmsg(
+                        String::from("Menu"),
+                        format!(
+                            "Search for String \"{}\" at Index {}",
+                            self.search_str, self.search_idx
+                        ),
+                    );
+                } else {
+                    module.sendmsg(
+                        String::from("Menu"),
+                        format!("Can't Find String \"{}\"", self.search_str),
+                    );
+                }
+            }
+            Key::Ctrl('g') => {
+                let target = MsgBox::new()
+                    .title("Go to Line[:Col]")
+                    .default_pos(module)
+                    .ask::<String>(module);
+                let target = match target {
+                    Some(Ok(target)) if !target.is_empty() => target,
+                    _ => return,
+                };
+                if !self.goto(&target, &module.term, &module.settings) {
+                    module.sendmsg(
+                        String::from("Menu"),
+                        format!("Invalid Position: `{target}`"),
+                    );
                 }
             }
             Key::Ctrl('s') => {
-                let curr_file = module.file_mod.name();
-                if curr_file.is_empty() {
-                    let ret = MsgBox::new()
-                        .title("Save as")
-                        .default_pos(module)
-                        .wait::<String>(module)
-                        .unwrap_or_default();
-                    if !ret.is_empty() {
-                        module.sendmsg(String::from("Menu"), format!("File \"{ret}\" Saved"));
-                        module.file_mod.set_name(ret);
-                        module.file_mod.save().unwrap();
-                    }
+                let id = module.file_mod.curr_id();
+                if module.file_mod.name().is_empty() {
+                    self.save_as(module, id);
                 } else {
-                    module.sendmsg(String::from("Menu"), format!("File \"{curr_file}\" Saved"));
-                    module.file_mod.save().unwrap();
+                    self.save(module, id, None);
                 }
             }
             Key::Char('\r') => {
-                self.push_line(term, settings);
+                let extension = file_mod.curr().extension();
+                self.each_caret(term, settings, |view, _| {
+                    view.delete_selection(term, settings);
+                    view.push_line(term, settings, extension);
+                });
             }
             Key::Char('\t') => {
-                self.push_str(utf16str!("    "));
+                let tab = Utf16String::from(" ".repeat(settings.indent_width));
+                self.each_caret(term, settings, |view, _| {
+                    view.delete_selection(term, settings);
+                    view.push_str(&tab);
+                });
             }
             Key::Char(char) => {
-                self.push(char);
+                self.each_caret(term, settings, |view, _| {
+                    view.delete_selection(term, settings);
+                    view.push(char, settings);
+                });
+            }
+            // Ctrl+]
+            Key::Ctrl('5') => {
+                self.jump_pair(term, settings);
+            }
+            Key::Backspace => {
+                self.each_caret(term, settings, |view, _| {
+                    if !view.delete_selection(term, settings) {
+                        view.delete(term, settings);
+                    }
+                });
             }
             Key::Delete => {
-                self.delete(term, settings);
+                self.each_caret(term, settings, |view, _| {
+                    if !view.delete_selection(term, settings) {
+                        view.delete_forward();
+                    }
+                });
             }
-            Key::Up => {
-                self.up(term, settings);
+            // Ctrl+Backspace（终端发送^H）
+            Key::Ctrl('h') => {
+                self.each_caret(term, settings, |view, _| {
+                    view.delete_word(term, settings);
+                });
             }
-            Key::Down => {
-                self.down(term, settings);
+            Key::Ctrl('c') => {
+                self.copy();
             }
-
-            Key::Left => {
-                self.left();
+            Key::Ctrl('x') => {
+                self.copy();
+                self.each_caret(term, settings, |view, _| {
+                    if !view.delete_selection(term, settings) {
+                        view.cut_line();
+                    }
+                });
+                self.follow(term, settings);
             }
-
-            Key::Right => {
-                self.right();
+            Key::Ctrl('v') => {
+                self.paste(term, settings);
             }
-
-            Key::Home => {
-                self.home();
+            Key::Ctrl('d') => {
+                self.select_next(term, settings);
             }
-
-            Key::End => {
-                self.end();
+            Key::Alt('l') => {
+                let count = self.select_matches(term, settings);
+                let msg = if count == 0 {
+                    String::from("No Match for the Last Search")
+                } else {
+                    format!("Add {count} Carets at \"{}\"", self.search_str)
+                };
+                module.sendmsg(String::from("Menu"), msg);
             }
-
-            Key::PageUp => {
-                for _ in [0; 25] {
-                    self.up(term, settings);
-                }
+            Key::Alt('c') => {
+                self.carets.clear();
             }
-
-            Key::PageDown => {
-                for _ in [0; 25] {
-                    self.down(term, settings);
-                }
+            Key::Insert => {
+                self.is_overwrite = !self.is_overwrite;
+                let mode = if self.is_overwrite { "OVR" } else { "INS" };
+                module.sendmsg(String::from("BottomBar"), String::from(mode));
+            }
+            Key::Ctrl('z') => {
+                let pos = (self.curr_line, self.curr_idx);
+                let pos = file_mod.mut_curr().undo(pos);
+                self.restore(pos, term, settings);
+            }
+            Key::Ctrl('y') => {
+                let pos = (self.curr_line, self.curr_idx);
+                let pos = file_mod.mut_curr().redo(pos);
+                self.restore(pos, term, settings);
             }
 
+            Key::Alt('d') => self.line_op_or_warn(module, LineOp::Duplicate),
+            Key::Ctrl('k') => self.line_op_or_warn(module, LineOp::Delete),
+            Key::Alt('j') => self.line_op_or_warn(module, LineOp::Join),
+            Key::Alt('s') => self.line_op_or_warn(module, LineOp::SortAsc),
+            Key::Alt('S') => self.line_op_or_warn(module, LineOp::SortDesc),
+            Key::Alt('n') => self.line_op_or_warn(module, LineOp::SortNum),
+            Key::Alt('u') => self.line_op_or_warn(module, LineOp::Unique),
+            Key::Alt('r') => self.line_op_or_warn(module, LineOp::Reverse),
+            Key::Alt('h') => self.line_op_or_warn(module, LineOp::Shuffle),
+
             Key::F(6) => {
                 let curr_pos = (self.curr_idx, self.curr_line);
                 let scroll = self.scroll;
@@ -625,42 +1808,118 @@ impl MainView {
             Key::F(8) => {
                 let curr_pos = (self.curr_idx, self.curr_line);
                 let scroll = self.scroll;
-                let file_id = MsgBox::new()
-                    .title("Input File Number")
+                // 按最近使用的顺序列出，第二项即为上一个文件
+                let files: Vec<(FileID, String)> = module
+                    .file_mod
+                    .recent()
+                    .into_iter()
+                    .filter_map(|id| {
+                        let file = module.file_mod.get(id)?;
+                        let name = match file.name() {
+                            "" => "[No Name]",
+                            name => name,
+                        };
+                        let dirty = if file.is_dirty() { " *" } else { "" };
+                        Some((id, format!("No.{id}  {name}{dirty}")))
+                    })
+                    .collect();
+                let items = files.iter().map(|(_, label)| label.clone()).collect();
+                let chosen = MsgBox::new()
+                    .title("Switch File")
                     .default_pos(module)
-                    .wait::<usize>(module)
-                    .unwrap_or_default();
-                let curr_id = module.file_mod.curr_id();
-                if file_id != 0 && file_id <= curr_id {
+                    .choose(module, items);
+                if let Some(file_id) = chosen.map(|idx| files[idx].0) {
                     module.sendmsg(
                         String::from("Menu"),
                         format!("Change to File No.{}", file_id),
                     );
 
-                    let new_status = module.file_mod.shift_to(file_id - 1, curr_pos, scroll);
+                    let new_status = module.file_mod.shift_to(file_id, curr_pos, scroll);
                     self.sync(&mut module.file_mod, new_status).unwrap();
                 }
             }
 
-            Key::Other(key) => {
-                match key[..] {
-                    // Alt(Left)
-                    [27, 91, 49, 59, 51, 68] => {
-                        self.resize(term, -1, 0, 0, 0);
-                        module.push_op(Op::Resize(String::from("FileTree"), (0, 0, -1, 0)));
-                        module.push_op(Op::Resize(String::from("TopBar"), (-1, 0, 0, 0)));
-                    }
-                    // Alt(Right)
-                    [27, 91, 49, 59, 51, 67] => {
-                        self.resize(term, 1, 0, 0, 0);
-                        module.push_op(Op::Resize(String::from("FileTree"), (0, 0, 1, 0)));
-                        module.push_op(Op::Resize(String::from("TopBar"), (1, 0, 0, 0)));
-                    }
-                    _ => (),
-                };
-            }
+            Key::Ctrl('w') => self.close(module, "close"),
+            Key::Ctrl('p') => self.find_file(module),
+            Key::Ctrl('o') => self.open_file(module),
+
+            key @ Key::Other(_) => match key::decode(&key) {
+                Some((Key::Left, Mods::ALT)) => {
+                    self.resize(term, -1, 0, 0, 0);
+                    module.push_op(Op::Resize(String::from("FileTree"), (0, 0, -1, 0)));
+                    module.push_op(Op::Resize(String::from("TopBar"), (-1, 0, 0, 0)));
+                }
+                Some((Key::Right, Mods::ALT)) => {
+                    self.resize(term, 1, 0, 0, 0);
+                    module.push_op(Op::Resize(String::from("FileTree"), (0, 0, 1, 0)));
+                    module.push_op(Op::Resize(String::from("TopBar"), (1, 0, 0, 0)));
+                }
+                Some((Key::Up, Mods::ALT)) => {
+                    self.line_op(LineOp::MoveUp, term, settings);
+                }
+                Some((Key::Down, Mods::ALT)) => {
+                    self.line_op(LineOp::MoveDown, term, settings);
+                }
+                Some((Key::Up, Mods::CTRL_ALT)) => {
+                    self.add_caret(false, term, settings);
+                }
+                Some((Key::Down, Mods::CTRL_ALT)) => {
+                    self.add_caret(true, term, settings);
+                }
+                Some((Key::Delete, Mods::CTRL)) => {
+                    self.each_caret(term, settings, |view, _| {
+                        view.delete_word_forward();
+                    });
+                }
+                _ => (),
+            },
 
             _ => (),
         }
     }
 }
+
+/// 文件无法打开时弹出错误信息
+fn open_error(module: &mut Module, err: std::io::Error) {
+    MsgBox::new()
+        .title("Error")
+        .default_pos(module)
+        .show(module, &format!("Can't Open: {err}"));
+}
+
+/// 把行内的码元下标转换为显示下标（制表符显示为4个空格）
+#[inline]
+fn display_idx(line: &Utf16Str, idx: usize) -> usize {
+    let tabs = line.as_slice()[..idx.min(line.len())]
+        .iter()
+        .filter(|unit| **unit == '\t' as u16)
+        .count();
+    idx + tabs * 3
+}
+
+/// 为子行上色，`offset`为子行在整行中的起始显示下标，
+/// `spans`中的区间会使用对应的背景色
+fn paint(
+    subline: &Utf16Str,
+    offset: usize,
+    spans: &[(usize, usize, &Color)],
+    bclr: &Color,
+    fclr: &Color,
+) -> String {
+    let mut painted = format!("{}{}", bclr.bclr_head(), fclr.fclr_head());
+    let mut curr: Option<&Color> = None;
+    for (i, ch) in subline.char_indices() {
+        let pos = offset + i;
+        let clr = spans
+            .iter()
+            .rev()
+            .find(|(start, end, _)| *start <= pos && pos < *end)
+            .map(|span| span.2);
+        if clr != curr {
+            painted += &clr.unwrap_or(bclr).bclr_head();
+            curr = clr;
+        }
+        painted.push(ch);
+    }
+    painted
+}
@@ -0,0 +1,61 @@
+/// 块（矩形）选择相关的辅助函数
+///
+/// 块的两端以`(行, 显示列)`记录，显示列可以超出行尾；
+/// 选中的每一行对应一个光标，由多光标完成输入、删除与复制
+use super::display_idx;
+use widestring::{Utf16Str, Utf16String};
+
+/// 块选择的两端，`cursor`为主光标所在的一端
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Block {
+    pub anchor: (usize, usize),
+    pub cursor: (usize, usize),
+}
+
+impl Block {
+    pub fn new(anchor: (usize, usize), cursor: (usize, usize)) -> Self {
+        Block { anchor, cursor }
+    }
+
+    /// 选中的行`[top, bottom]`
+    #[inline]
+    pub fn rows(&self) -> (usize, usize) {
+        let (a, c) = (self.anchor.0, self.cursor.0);
+        (a.min(c), a.max(c))
+    }
+
+    /// 选中的显示列`[left, right)`
+    #[inline]
+    pub fn cols(&self) -> (usize, usize) {
+        let (a, c) = (self.anchor.1, self.cursor.1);
+        (a.min(c), a.max(c))
+    }
+}
+
+/// 显示列`col`对应的码元下标，超出行尾时返回行尾
+///
+/// 显示列落在制表符中间时返回制表符之后的下标
+pub fn unit_idx(line: &Utf16Str, col: usize) -> usize {
+    (0..=line.len())
+        .find(|idx| display_idx(line, *idx) >= col)
+        .unwrap_or(line.len())
+}
+
+/// 用空格把行补齐到显示列`col`
+pub fn pad(line: &mut Utf16String, col: usize) {
+    let width = display_idx(line, line.len());
+    if width < col {
+        line.push_str(&" ".repeat(col - width));
+    }
+}
+
+/// 取出行中显示列`[left, right)`的内容，不足的部分用空格补齐
+pub fn slice(line: &Utf16Str, left: usize, right: usize) -> String {
+    let (start, end) = (unit_idx(line, left), unit_idx(line, right));
+    let mut text = line[start..end].to_string();
+    let width = display_idx(line, end) - display_idx(line, start);
+    if width < right - left {
+        text.push_str(&" ".repeat(right - left - width));
+    }
+    text
+}
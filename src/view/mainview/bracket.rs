@@ -0,0 +1,130 @@
+/// 括号自动配对与匹配相关的辅助函数
+///
+/// 括号与引号均为单个UTF-16码元，因此可以直接按码元扫描
+use widestring::{Utf16Str, Utf16String};
+
+const OPENERS: [char; 3] = ['(', '[', '{'];
+const CLOSERS: [char; 3] = [')', ']', '}'];
+const QUOTES: [char; 3] = ['"', '\'', '`'];
+/// 绘制时寻找匹配括号，在视图高度之外最多多扫描的行数
+pub const SCAN_MARGIN: usize = 1000;
+
+/// 输入字符时自动补全的右侧字符
+#[inline]
+pub fn auto_closer(ch: char) -> Option<char> {
+    if let Some(i) = OPENERS.iter().position(|c| *c == ch) {
+        Some(CLOSERS[i])
+    } else if QUOTES.contains(&ch) {
+        Some(ch)
+    } else {
+        None
+    }
+}
+
+#[inline]
+pub fn is_closer(ch: char) -> bool {
+    CLOSERS.contains(&ch) || QUOTES.contains(&ch)
+}
+
+/// 判断在`idx`处输入`ch`时是否应当补全右侧字符
+///
+/// 仅当光标后为空白、行尾或右括号时补全；
+/// 引号前紧跟字母或数字时（如`don't`）不补全
+pub fn should_pair(line: &Utf16Str, idx: usize, ch: char) -> bool {
+    let next = char_at(line, idx);
+    let is_free = next.is_none_or(|c| c.is_whitespace() || CLOSERS.contains(&c));
+    if !is_free {
+        return false;
+    }
+    if QUOTES.contains(&ch) {
+        let prev = idx.checked_sub(1).and_then(|i| char_at(line, i));
+        return !prev.is_some_and(|c| c.is_alphanumeric() || c == ch);
+    }
+    true
+}
+
+/// 判断光标是否位于一对空括号/引号之间
+pub fn is_empty_pair(line: &Utf16Str, idx: usize) -> bool {
+    let prev = idx.checked_sub(1).and_then(|i| char_at(line, i));
+    let next = char_at(line, idx);
+    match (prev.and_then(auto_closer), next) {
+        (Some(closer), Some(next)) => closer == next,
+        _ => false,
+    }
+}
+
+/// 取出`idx`处的单个码元字符
+#[inline]
+pub fn char_at(line: &Utf16Str, idx: usize) -> Option<char> {
+    line.as_slice()
+        .get(idx)
+        .and_then(|unit| char::from_u32(*unit as u32))
+}
+
+/// 寻找光标处括号的匹配括号
+///
+/// 优先检查光标下的字符，其次检查光标前的字符，最多向前或向后扫描`limit`行
+/// 返回值为`(括号位置, 匹配括号位置)`，位置形如`(行, 下标)`
+pub fn find_pair(
+    content: &[Utf16String],
+    line: usize,
+    idx: usize,
+    limit: usize,
+) -> Option<((usize, usize), (usize, usize))> {
+    let curr = content.get(line)?;
+    let candidates = [Some(idx), idx.checked_sub(1)];
+    for pos in candidates.into_iter().flatten() {
+        if let Some(ch) = char_at(curr, pos) {
+            if let Some(target) = find_match(content, line, pos, ch, limit) {
+                return Some(((line, pos), target));
+            }
+        }
+    }
+    None
+}
+
+/// 从`(line, idx)`处的括号`ch`出发，跨行寻找匹配的括号
+fn find_match(
+    content: &[Utf16String],
+    line: usize,
+    idx: usize,
+    ch: char,
+    limit: usize,
+) -> Option<(usize, usize)> {
+    if let Some(i) = OPENERS.iter().position(|c| *c == ch) {
+        let (open, close) = (ch as u16, CLOSERS[i] as u16);
+        let mut depth = 0usize;
+        let lines = content.iter().enumerate().skip(line);
+        for (l, text) in lines.take(limit.saturating_add(1)) {
+            let start = if l == line { idx } else { 0 };
+            for (i, unit) in text.as_slice().iter().enumerate().skip(start) {
+                if *unit == open {
+                    depth += 1;
+                } else if *unit == close {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some((l, i));
+                    }
+                }
+            }
+        }
+    } else if let Some(i) = CLOSERS.iter().position(|c| *c == ch) {
+        let (open, close) = (OPENERS[i] as u16, ch as u16);
+        let mut depth = 0usize;
+        for l in (line.saturating_sub(limit)..=line).rev() {
+            let text = content[l].as_slice();
+            let end = if l == line { idx + 1 } else { text.len() };
+            for i in (0..end).rev() {
+                if text[i] == close {
+                    depth += 1;
+                } else if text[i] == open {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some((l, i));
+                    }
+                }
+            }
+        }
+    }
+    None
+}
@@ -0,0 +1,88 @@
+/// 多光标相关的辅助函数
+///
+/// 多个光标从后往前依次编辑，已编辑过的光标以到文末的距离`(行数, 码元数)`记录：
+/// 编辑只会改动光标之前的内容，因此到文末的距离在之后的编辑中保持不变
+use widestring::{Utf16Str, Utf16String};
+
+/// 光标与选中区域的另一端，位置形如`(行, 下标)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Caret {
+    pub pos: (usize, usize),
+    pub anchor: Option<(usize, usize)>,
+}
+
+impl Caret {
+    pub fn new(pos: (usize, usize), anchor: Option<(usize, usize)>) -> Self {
+        Caret { pos, anchor }
+    }
+
+    /// 选中区域的起点，没有选中时为光标位置
+    #[inline]
+    pub fn start(&self) -> (usize, usize) {
+        self.anchor.map_or(self.pos, |anchor| anchor.min(self.pos))
+    }
+
+    /// 按位置排序的选中区域
+    #[inline]
+    pub fn selection(&self) -> Option<((usize, usize), (usize, usize))> {
+        let anchor = self.anchor?;
+        if anchor == self.pos {
+            return None;
+        }
+        Some((anchor.min(self.pos), anchor.max(self.pos)))
+    }
+}
+
+/// 把位置转换为到文末的距离
+#[inline]
+pub fn to_end(content: &[Utf16String], (line, idx): (usize, usize)) -> (usize, usize) {
+    let line = line.min(content.len() - 1);
+    (
+        content.len() - line,
+        content[line].len().saturating_sub(idx),
+    )
+}
+
+/// 把到文末的距离还原为位置
+#[inline]
+pub fn from_end(content: &[Utf16String], (lines, units): (usize, usize)) -> (usize, usize) {
+    let line = content.len().saturating_sub(lines);
+    (line, content[line].len().saturating_sub(units))
+}
+
+/// 取出选中区域的文本，跨行时以`\n`连接
+pub fn text(content: &[Utf16String], (sl, si): (usize, usize), (el, ei): (usize, usize)) -> String {
+    if sl == el {
+        return content[sl][si..ei].to_string();
+    }
+    let mut text = content[sl][si..].to_string();
+    for line in &content[sl + 1..el] {
+        text.push('\n');
+        text.push_str(&line.to_string());
+    }
+    text.push('\n');
+    text.push_str(&content[el][..ei].to_string());
+    text
+}
+
+/// 在内容中查找`needle`的所有出现位置（UTF-16下标）
+pub fn find_all(content: &[Utf16String], needle: &Utf16Str) -> Vec<(usize, usize)> {
+    let needle = needle.as_slice();
+    if needle.is_empty() {
+        return Vec::new();
+    }
+    let mut found = Vec::new();
+    for (l, line) in content.iter().enumerate() {
+        let line = line.as_slice();
+        let mut i = 0;
+        while i + needle.len() <= line.len() {
+            if line[i..i + needle.len()] == *needle {
+                found.push((l, i));
+                i += needle.len();
+            } else {
+                i += 1;
+            }
+        }
+    }
+    found
+}
@@ -0,0 +1,102 @@
+/// <Ctrl+p>查找文件的候选项
+///
+/// - 打开的文件按最近使用的顺序加分，当前文件不加分
+/// - 其余文件由后台线程遍历工作区得到，分批发送给`Picker`，
+///   跳过以`.`开头的目录与符号链接指向的目录
+///
+/// 候选项的文本为相对于当前目录的路径，不在当前目录下时为绝对路径
+use crate::file::FileMod;
+use crate::view::picker::Item;
+use crossbeam_channel::{unbounded, Receiver};
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    thread,
+};
+
+/// 每批发送的文件数
+const BATCH: usize = 4096;
+/// 最近使用的文件的最大加分
+const BONUS_RECENT: i64 = 48;
+
+/// 候选项文本对应的路径
+pub fn path(file_mod: &FileMod, label: &str) -> PathBuf {
+    file_mod.curr_dir().join(label)
+}
+
+fn label(dir: &Path, path: &Path) -> String {
+    path.strip_prefix(dir)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// 打开的文件中有路径的部分
+pub fn buffers(file_mod: &FileMod) -> Vec<Item> {
+    let recent = file_mod.recent();
+    let total = recent.len() as i64;
+    recent
+        .iter()
+        .enumerate()
+        .filter_map(|(rank, id)| {
+            let file = file_mod.get(*id)?;
+            if file.pathbuf().as_os_str().is_empty() {
+                return None;
+            }
+            let bonus = if rank == 0 {
+                0
+            } else {
+                BONUS_RECENT * (total - rank as i64 + 1) / total
+            };
+            let label = label(file_mod.curr_dir(), file.pathbuf());
+            Some(Item::new(label, format!("No.{id}"), bonus))
+        })
+        .collect()
+}
+
+/// 在后台遍历工作区，已经打开的文件不会重复发送
+pub fn walk(file_mod: &FileMod) -> Receiver<Vec<Item>> {
+    let (sender, receiver) = unbounded();
+    let dir = file_mod.curr_dir().clone();
+    let mut stack: Vec<PathBuf> = file_mod.roots().to_vec();
+    let opened: HashSet<PathBuf> = file_mod
+        .ids()
+        .into_iter()
+        .filter_map(|id| file_mod.get(id).map(|file| file.pathbuf().clone()))
+        .collect();
+
+    thread::spawn(move || {
+        let mut batch = Vec::with_capacity(BATCH);
+        while let Some(curr) = stack.pop() {
+            let entries = match fs::read_dir(&curr) {
+                Ok(entries) => entries,
+                Err(_) => continue,
+            };
+            for entry in entries.flatten() {
+                let file_type = match entry.file_type() {
+                    Ok(file_type) => file_type,
+                    Err(_) => continue,
+                };
+                let path = entry.path();
+                if file_type.is_dir() {
+                    if !entry.file_name().to_string_lossy().starts_with('.') {
+                        stack.push(path);
+                    }
+                } else if !opened.contains(&path) {
+                    batch.push(Item::new(label(&dir, &path), String::new(), 0));
+                    if batch.len() == BATCH {
+                        // 弹窗关闭后停止遍历
+                        if sender.send(std::mem::take(&mut batch)).is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+        if !batch.is_empty() {
+            let _ = sender.send(batch);
+        }
+    });
+    receiver
+}
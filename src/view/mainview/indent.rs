@@ -0,0 +1,84 @@
+/// 自动缩进相关的辅助函数
+///
+/// 换行时沿用上一行的缩进，在左括号或特定语言的块起始符之后多缩进一级
+use widestring::{Utf16Str, Utf16String};
+
+/// 左括号与右括号的对应关系
+const PAIRS: [(char, char); 3] = [('(', ')'), ('[', ']'), ('{', '}')];
+
+/// 根据文件扩展名得到该语言的块起始关键字（位于行尾）
+fn block_openers(extension: Option<&str>) -> &'static [&'static str] {
+    match extension {
+        Some("py") => &[":"],
+        Some("lua") => &["then", "do", "else", "repeat", ")"],
+        Some("hs") => &["where", "do", "of", "let", "="],
+        Some("sh" | "bash" | "zsh") => &["then", "do", "else"],
+        Some("rb") => &["do", "then", "else", "begin"],
+        _ => &[],
+    }
+}
+
+/// 返回左括号对应的右括号
+#[inline]
+pub fn closer(open: char) -> Option<char> {
+    PAIRS
+        .iter()
+        .find_map(|&(o, c)| if o == open { Some(c) } else { None })
+}
+
+/// 取出行首的空白部分
+pub fn leading(line: &Utf16Str) -> Utf16String {
+    line.chars()
+        .take_while(|ch| *ch == ' ' || *ch == '\t')
+        .collect::<String>()
+        .into()
+}
+
+/// 生成一级缩进，若该行原本使用制表符缩进则沿用制表符
+pub fn unit(leading: &Utf16Str, width: usize) -> Utf16String {
+    if leading.chars().next() == Some('\t') {
+        Utf16String::from("\t")
+    } else {
+        Utf16String::from(" ".repeat(width))
+    }
+}
+
+/// 判断光标前的内容是否开启了新的一级缩进
+///
+/// `Lua`中的`function foo()`以右括号结尾，因此需要额外判断是否含有`function`
+pub fn opens_block(before: &Utf16Str, extension: Option<&str>) -> bool {
+    let before = before.to_string();
+    let trimmed = before.trim_end();
+    let last = match trimmed.chars().last() {
+        Some(ch) => ch,
+        None => return false,
+    };
+    if closer(last).is_some() {
+        return true;
+    }
+
+    block_openers(extension).iter().any(|opener| {
+        if !trimmed.ends_with(opener) {
+            return false;
+        }
+        if *opener == ")" {
+            return trimmed.contains("function");
+        }
+        // 关键字需要在单词边界上
+        let rest = &trimmed[..trimmed.len() - opener.len()];
+        !opener.chars().all(char::is_alphanumeric)
+            || !rest
+                .chars()
+                .last()
+                .is_some_and(|ch| ch.is_alphanumeric() || ch == '_')
+    })
+}
+
+/// 删除缩进时需要删除的空格数，光标前必须全为空格
+pub fn dedent_len(before: &Utf16Str, width: usize) -> Option<usize> {
+    if before.is_empty() || width == 0 || !before.chars().all(|ch| ch == ' ') {
+        return None;
+    }
+    let len = before.len();
+    Some((len - 1) % width + 1)
+}
@@ -0,0 +1,49 @@
+/// 跳转位置的解析
+///
+/// 支持以下形式，行号与列号从1开始：
+/// - `<line>[:<col>]`：跳转到指定行（与列）
+/// - `+N`/`-N`：相对当前行向下/向上跳转`N`行
+/// - `N%`：跳转到文件的百分比位置
+use std::str::FromStr;
+
+/// 解析后的跳转目标
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Jump {
+    Line(usize, Option<usize>),
+    Down(usize),
+    Up(usize),
+    Percent(usize),
+}
+
+impl FromStr for Jump {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let num = |s: &str| s.parse::<usize>().map_err(|_| ());
+        if let Some(n) = s.strip_prefix('+') {
+            Ok(Jump::Down(num(n)?))
+        } else if let Some(n) = s.strip_prefix('-') {
+            Ok(Jump::Up(num(n)?))
+        } else if let Some(n) = s.strip_suffix('%') {
+            Ok(Jump::Percent(num(n)?.min(100)))
+        } else if let Some((line, col)) = s.split_once(':') {
+            Ok(Jump::Line(num(line)?, Some(num(col)?)))
+        } else {
+            Ok(Jump::Line(num(s)?, None))
+        }
+    }
+}
+
+impl Jump {
+    /// 计算目标行（从0开始）与列（从0开始，未指定时为`None`），行号会被限制在`[0, total)`内
+    pub fn resolve(&self, curr: usize, total: usize) -> (usize, Option<usize>) {
+        let last = total.saturating_sub(1);
+        let (line, col) = match *self {
+            Jump::Line(line, col) => (line.saturating_sub(1), col.map(|c| c.saturating_sub(1))),
+            Jump::Down(n) => (curr.saturating_add(n), None),
+            Jump::Up(n) => (curr.saturating_sub(n), None),
+            Jump::Percent(p) => (last * p / 100, None),
+        };
+        (line.min(last), col)
+    }
+}
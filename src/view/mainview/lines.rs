@@ -0,0 +1,163 @@
+/// 行操作：复制、删除、移动、合并、排序、去重、反转与打乱
+///
+/// 所有操作都作用于`[start, end]`闭区间内的行
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+use widestring::Utf16String;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineOp {
+    Duplicate,
+    Delete,
+    MoveUp,
+    MoveDown,
+    Join,
+    SortAsc,
+    SortDesc,
+    SortNum,
+    Unique,
+    Reverse,
+    Shuffle,
+}
+
+impl LineOp {
+    /// 是否只作用于选中的行：排序、去重、反转与打乱单独作用于一行没有意义
+    pub fn needs_selection(&self) -> bool {
+        matches!(
+            self,
+            LineOp::SortAsc
+                | LineOp::SortDesc
+                | LineOp::SortNum
+                | LineOp::Unique
+                | LineOp::Reverse
+                | LineOp::Shuffle
+        )
+    }
+}
+
+impl FromStr for LineOp {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let op = match s {
+            "duplicate" => LineOp::Duplicate,
+            "deleteline" => LineOp::Delete,
+            "moveup" => LineOp::MoveUp,
+            "movedown" => LineOp::MoveDown,
+            "join" => LineOp::Join,
+            "sort" => LineOp::SortAsc,
+            "sortdesc" => LineOp::SortDesc,
+            "sortnum" => LineOp::SortNum,
+            "unique" => LineOp::Unique,
+            "reverse" => LineOp::Reverse,
+            "shuffle" => LineOp::Shuffle,
+            _ => return Err(()),
+        };
+        Ok(op)
+    }
+}
+
+/// 复制区间内的行到其下方
+pub fn duplicate(content: &mut Vec<Utf16String>, start: usize, end: usize) {
+    let copies = content[start..=end].to_vec();
+    content.splice(end + 1..end + 1, copies);
+}
+
+/// 删除区间内的行，至少保留一个空行
+pub fn delete(content: &mut Vec<Utf16String>, start: usize, end: usize) {
+    content.drain(start..=end);
+    if content.is_empty() {
+        content.push(Utf16String::new());
+    }
+}
+
+/// 把区间内的行上移一行，成功时返回`true`
+pub fn move_up(content: &mut [Utf16String], start: usize, end: usize) -> bool {
+    if start == 0 {
+        return false;
+    }
+    content[start - 1..=end].rotate_left(1);
+    true
+}
+
+/// 把区间内的行下移一行，成功时返回`true`
+pub fn move_down(content: &mut [Utf16String], start: usize, end: usize) -> bool {
+    if end + 1 >= content.len() {
+        return false;
+    }
+    content[start..=end + 1].rotate_right(1);
+    true
+}
+
+/// 合并区间内的行，连接处的空白被压缩为一个空格
+///
+/// 区间只有一行时与下一行合并，返回最后一个连接处的下标
+pub fn join(content: &mut Vec<Utf16String>, start: usize, end: usize) -> Option<usize> {
+    let end = if start == end { end + 1 } else { end };
+    if end >= content.len() {
+        return None;
+    }
+    let mut joined = content[start].to_string().trim_end().to_string();
+    let mut idx = joined.encode_utf16().count();
+    for line in content.drain(start + 1..=end) {
+        let line = line.to_string();
+        let line = line.trim_start();
+        idx = joined.encode_utf16().count();
+        if !line.is_empty() && !joined.is_empty() {
+            joined.push(' ');
+        }
+        joined.push_str(line);
+    }
+    content[start] = joined.into();
+    Some(idx)
+}
+
+/// 对区间内的行排序、去重、反转或打乱
+pub fn reorder(content: &mut Vec<Utf16String>, start: usize, end: usize, op: LineOp) {
+    let lines = &mut content[start..=end];
+    match op {
+        LineOp::SortAsc => lines.sort(),
+        LineOp::SortDesc => lines.sort_by(|a, b| b.cmp(a)),
+        LineOp::SortNum => lines.sort_by(|a, b| {
+            let (a, b) = (leading_num(a), leading_num(b));
+            a.partial_cmp(&b).unwrap_or(std::cmp::Ordering::Equal)
+        }),
+        LineOp::Reverse => lines.reverse(),
+        LineOp::Shuffle => shuffle(lines),
+        LineOp::Unique => {
+            let mut seen = std::collections::HashSet::new();
+            let unique: Vec<Utf16String> = content
+                .drain(start..=end)
+                .filter(|line| seen.insert(line.clone()))
+                .collect();
+            content.splice(start..start, unique);
+        }
+        _ => (),
+    }
+}
+
+/// 取出行首的数字，没有数字的行排在最前
+fn leading_num(line: &Utf16String) -> Option<f64> {
+    let line = line.to_string();
+    let line = line.trim_start();
+    let len = line
+        .char_indices()
+        .take_while(|(i, ch)| ch.is_ascii_digit() || *ch == '.' || (*i == 0 && *ch == '-'))
+        .count();
+    line[..len].parse::<f64>().ok()
+}
+
+/// 通过xorshift生成随机数，用Fisher-Yates算法打乱
+fn shuffle(lines: &mut [Utf16String]) {
+    let mut seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_nanos() as u64)
+        .unwrap_or(0x2545f4914f6cdd1d)
+        | 1;
+    for i in (1..lines.len()).rev() {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        let j = (seed % (i as u64 + 1)) as usize;
+        lines.swap(i, j);
+    }
+}
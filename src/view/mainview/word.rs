@@ -0,0 +1,137 @@
+/// 按单词移动与删除相关的辅助函数
+///
+/// 一行被切分为若干片段，空白不属于任何片段：
+/// - 单词：字母、数字与下划线的连续序列
+/// - 标点：其他符号的连续序列
+/// - 中日韩文字：汉字、假名与谚文的连续序列
+///
+/// 按子词移动时，单词会在下划线、大小写变化（`camelCase`、`HTTPServer`）
+/// 与字母数字交界处进一步切分
+use widestring::Utf16Str;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Class {
+    Space,
+    Punct,
+    Word,
+    Cjk,
+}
+
+#[inline]
+fn is_cjk(ch: char) -> bool {
+    matches!(ch as u32,
+        0x3040..=0x30ff       // 平假名、片假名
+        | 0x3400..=0x4dbf     // 汉字扩展A
+        | 0x4e00..=0x9fff     // 汉字
+        | 0xac00..=0xd7af     // 谚文
+        | 0xf900..=0xfaff     // 兼容汉字
+        | 0x20000..=0x3134f   // 汉字扩展B~G
+    )
+}
+
+#[inline]
+fn class(ch: char) -> Class {
+    if ch.is_whitespace() {
+        Class::Space
+    } else if is_cjk(ch) {
+        Class::Cjk
+    } else if ch.is_alphanumeric() || ch == '_' {
+        Class::Word
+    } else {
+        Class::Punct
+    }
+}
+
+/// 把一行切分为片段，返回各片段的`[start, end)`（UTF-16下标）
+fn segments(line: &Utf16Str, is_sub: bool) -> Vec<(usize, usize)> {
+    let chars: Vec<(usize, char)> = line.char_indices().collect();
+    let mut segments = Vec::new();
+    let mut start = 0;
+    while start < chars.len() {
+        let kind = class(chars[start].1);
+        let mut end = start + 1;
+        while end < chars.len() && class(chars[end].1) == kind {
+            end += 1;
+        }
+        match kind {
+            Class::Space => (),
+            Class::Word if is_sub => split_word(&chars[start..end], line.len(), &mut segments),
+            _ => segments.push((chars[start].0, pos_of(&chars, end, line.len()))),
+        }
+        start = end;
+    }
+    segments
+}
+
+/// 把单词切分为子词
+fn split_word(chars: &[(usize, char)], len: usize, segments: &mut Vec<(usize, usize)>) {
+    // 全为下划线时视作一个片段，避免光标无法移动
+    if chars.iter().all(|(_, ch)| *ch == '_') {
+        segments.push((chars[0].0, pos_of(chars, chars.len(), len)));
+        return;
+    }
+
+    let mut start: Option<usize> = None;
+    for k in 0..chars.len() {
+        let ch = chars[k].1;
+        if ch == '_' {
+            if let Some(s) = start.take() {
+                segments.push((chars[s].0, chars[k].0));
+            }
+            continue;
+        }
+        if let Some(s) = start {
+            let prev = chars[k - 1].1;
+            let next = chars.get(k + 1).map(|c| c.1);
+            let is_boundary = (prev.is_lowercase() && ch.is_uppercase())
+                || (prev.is_uppercase()
+                    && ch.is_uppercase()
+                    && next.is_some_and(|c| c.is_lowercase()))
+                || (prev.is_numeric() != ch.is_numeric());
+            if is_boundary {
+                segments.push((chars[s].0, chars[k].0));
+                start = Some(k);
+            }
+        } else {
+            start = Some(k);
+        }
+    }
+    if let Some(s) = start {
+        segments.push((chars[s].0, pos_of(chars, chars.len(), len)));
+    }
+}
+
+/// 第`k`个字符的下标，越界时返回片段的末尾
+#[inline]
+fn pos_of(chars: &[(usize, char)], k: usize, len: usize) -> usize {
+    match chars.get(k) {
+        Some((pos, _)) => *pos,
+        None => chars.last().map_or(len, |(pos, ch)| pos + ch.len_utf16()),
+    }
+}
+
+/// 光标之后第一个片段的末尾，没有时返回行尾
+pub fn next(line: &Utf16Str, idx: usize, is_sub: bool) -> usize {
+    segments(line, is_sub)
+        .into_iter()
+        .map(|(_, end)| end)
+        .find(|end| *end > idx)
+        .unwrap_or(line.len())
+}
+
+/// 光标之前最后一个片段的开头，没有时返回行首
+pub fn prev(line: &Utf16Str, idx: usize, is_sub: bool) -> usize {
+    segments(line, is_sub)
+        .into_iter()
+        .map(|(start, _)| start)
+        .rfind(|start| *start < idx)
+        .unwrap_or(0)
+}
+
+/// 光标所在或紧邻光标左侧的单词，返回其`[start, end)`
+pub fn around(line: &Utf16Str, idx: usize) -> Option<(usize, usize)> {
+    segments(line, false).into_iter().find(|(start, end)| {
+        let first = line[*start..].chars().next();
+        *start <= idx && idx <= *end && first.is_some_and(|ch| class(ch) == Class::Word)
+    })
+}
@@ -1,9 +1,27 @@
 /// Menu为最上方的状态条
 ///
 /// 在被聚焦时可以输入命令，通过<Enter>来提交命令并尝试执行
+/// 命令的语法见`command`，错误与用法会显示在菜单中
+/// <Up>/<Down>浏览命令历史，<Tab>补全命令名、设置项、主题与路径，
+/// 有多个候选项时在下方弹出列表，再按<Tab>/<Shift+Tab>依次选择
 /// 在其他时间会根据目前的状态显示一些信息
 use crate::prelude::*;
+use crate::settings::Theme;
 use getch_rs::Key;
+use std::{
+    path::PathBuf,
+    process::{self, Stdio},
+};
+
+mod command;
+mod complete;
+
+use super::lineedit::LineEdit;
+use command::Command;
+use complete::Completion;
+
+/// 补全列表最多显示的行数
+const POPUP_ROWS: usize = 8;
 
 #[view("Menu")]
 #[start=(1, 1)]
@@ -13,8 +31,13 @@ pub struct Menu {
     search_bclr: Color,
     search_fclr: Color,
     content: String,
-    input: String,
-    input_idx: usize,
+    input: LineEdit,
+    /// 聚焦时收到的消息，如命令的结果，在下一次按键时清除
+    result: Option<String>,
+    /// 弹出的补全列表
+    completion: Completion,
+    /// 补全列表中选中的候选项
+    selected: Option<usize>,
 }
 
 impl Default for Menu {
@@ -31,8 +54,12 @@ impl View for Menu {
         self.search_fclr = settings.theme.bright_white.clone();
         self.fcolor = settings.theme.stress_fclr.clone();
         self.offset = 15;
+        self.input.history("history");
     }
     fn update(&mut self, module: &mut Module) {
+        if module.curr_view != self.name {
+            self.close_popup();
+        }
         let (bclr, fclr, sclr) = (&self.bcolor, &self.fcolor, &self.search_bclr);
         let offset = self.offset;
         let term = &module.term;
@@ -58,13 +85,17 @@ impl View for Menu {
             20
         };
 
+        self.input.set_width(search_len - 4);
         let search_content = if module.curr_view == self.name {
-            let recv = module.recvmsg(&self.name).unwrap_or_default();
-            self.input_idx += recv.len();
-            self.input.push_str(&recv);
-            &self.input
+            if let Some(msg) = module.recvmsg(&self.name) {
+                self.result = Some(msg);
+            }
+            match &self.result {
+                Some(result) if self.input.is_empty() => result.clone(),
+                _ => self.input.render(),
+            }
         } else {
-            &module
+            module
                 .recvmsg(&self.name)
                 .unwrap_or(String::from("Press <F1> for help"))
         };
@@ -86,45 +117,28 @@ impl View for Menu {
         self.content = content;
     }
     fn matchar(&mut self, module: &mut Module, key: getch_rs::Key) {
-        let term = &module.term;
-        let offset = self.offset;
-        let max = self.get_end(term).0 - self.get_start(term).0;
-        match key {
-            Key::Char('\r') => {
-                self.exec(module);
+        self.result = None;
+        if !self.completion.items.is_empty() {
+            match key {
+                Key::Char('\t') | Key::Down => return self.select(true),
+                Key::BackTab | Key::Up => return self.select(false),
+                Key::Char('\r') if self.selected.is_some() => return self.close_popup(),
+                _ => self.close_popup(),
             }
-            Key::Char(ch) => {
-                if self.input.len() < max as usize - 2 * offset - 4 {
-                    self.input.insert(self.input_idx, ch);
-                    self.input_idx += 1;
-                }
-            }
-            Key::Delete => {
-                if !self.input.is_empty() {
-                    self.input_idx -= 1;
-                    self.input.remove(self.input_idx);
-                }
-            }
-
-            Key::Left => {
-                if self.input_idx > 0 {
-                    self.input_idx -= 1;
-                }
-            }
-
-            Key::Right => {
-                if self.input_idx < self.input.len() {
-                    self.input_idx += 1;
-                }
+        }
+        match key {
+            Key::Char('\r') => self.exec(module),
+            Key::Char('\t') => self.complete(module),
+            key => {
+                self.input.handle(&key);
             }
-            _ => (),
         }
     }
     fn set_cursor(&self, module: &mut Module) {
         let term = &module.term;
         let offset = self.offset;
         let (x, y) = self.get_start(term);
-        let csr_x = x + offset as u16 + 3 + self.input_idx as u16;
+        let csr_x = x + offset as u16 + 3 + self.input.cursor_col() as u16;
         Cursor::set_csr(csr_x, y);
     }
     fn draw(&self, module: &mut Module) -> std::io::Result<()> {
@@ -133,6 +147,7 @@ impl View for Menu {
         let (x, y) = self.get_start(term);
         Cursor::set_csr(x, y);
         print!("{}", self.content);
+        self.draw_popup(module);
         io::stdout().flush()?;
         Ok(())
     }
@@ -140,25 +155,271 @@ impl View for Menu {
 
 impl Menu {
     fn exec(&mut self, module: &mut Module) {
-        let cmd = self.input.trim();
-        match cmd {
-            "quit" => {
-                module.push_op(Op::Quit);
-            }
-            "save" => {
-                let name = &module.file_mod.name();
-                if !name.is_empty() {
-                    module.sendmsg(String::from("Menu"), format!("File \"{name}\" Saved"));
-                    module.file_mod.save().unwrap();
+        let input = self.input.submit().trim().to_string();
+        if input.is_empty() {
+            return;
+        }
+
+        let result = match Command::parse(&input) {
+            Ok(cmd) => self.run(module, &cmd),
+            Err(err) => Err(err.to_string()),
+        };
+        if let Err(msg) = result {
+            module.sendmsg(String::from("Menu"), msg);
+        }
+    }
+
+    /// 补全光标前的词，只有一个候选项时直接补全，否则补全公共前缀并弹出列表
+    fn complete(&mut self, module: &Module) {
+        let completion =
+            complete::complete(&self.input.before_cursor(), module.file_mod.curr_dir());
+        match &completion.items[..] {
+            [] => (),
+            [item] => {
+                let item = if item.ends_with('/') {
+                    item.clone()
                 } else {
-                    module.sendmsg(String::from("Menu"), String::from("Use <Ctrl+s>"));
+                    format!("{item} ")
+                };
+                self.replace_word(completion.start, &item);
+            }
+            _ => {
+                self.replace_word(completion.start, &completion.common_prefix());
+                self.completion = completion;
+            }
+        }
+    }
+
+    /// 在补全列表中选择下一个/上一个候选项并填入输入
+    fn select(&mut self, is_next: bool) {
+        let len = self.completion.items.len();
+        let selected = match (self.selected, is_next) {
+            (None, true) => 0,
+            (None, false) => len - 1,
+            (Some(idx), true) => (idx + 1) % len,
+            (Some(idx), false) => (idx + len - 1) % len,
+        };
+        self.selected = Some(selected);
+        let item = self.completion.items[selected].clone();
+        self.replace_word(self.completion.start, &item);
+    }
+
+    /// 用`word`替换`start`到光标之间的内容
+    fn replace_word(&mut self, start: usize, word: &str) {
+        self.input.replace(start..self.input.cursor(), word);
+    }
+
+    fn close_popup(&mut self) {
+        self.completion = Completion::default();
+        self.selected = None;
+    }
+
+    /// 在输入框下方绘制补全列表
+    fn draw_popup(&self, module: &Module) {
+        let items = &self.completion.items;
+        if items.is_empty() {
+            return;
+        }
+        let term = &module.term;
+        let (x, y) = self.get_start(term);
+        let col = x + self.offset as u16 + 3 + self.input.col(self.completion.start) as u16;
+        let width = items
+            .iter()
+            .map(|item| item.chars().count())
+            .max()
+            .unwrap_or(0)
+            + 2;
+        let width = width.min((term.width + 1).saturating_sub(col) as usize);
+        let selected = self.selected.unwrap_or(0);
+        let scroll = (selected + 1).saturating_sub(POPUP_ROWS);
+
+        for (row, (idx, item)) in items
+            .iter()
+            .enumerate()
+            .skip(scroll)
+            .take(POPUP_ROWS)
+            .enumerate()
+        {
+            let bclr = if self.selected == Some(idx) {
+                &self.search_bclr
+            } else {
+                &self.bcolor
+            };
+            let item: String = item.chars().take(width.saturating_sub(2)).collect();
+            Cursor::set_csr(col, y + 1 + row as u16);
+            print!(
+                "{}{} {:<w$} {END}",
+                bclr.bclr_head(),
+                self.search_fclr.fclr_head(),
+                item,
+                w = width.saturating_sub(2)
+            );
+        }
+    }
+
+    /// 执行命令，参数不对时返回用法，失败时返回原因
+    fn run(&mut self, module: &mut Module, cmd: &Command) -> Result<(), String> {
+        let args: Vec<&str> = cmd.args.iter().map(String::as_str).collect();
+        match (cmd.name, &args[..]) {
+            ("quit", []) => module.push_op(Op::Quit),
+            ("help", []) => module.push_op(Op::Shift(String::from("Help"))),
+            ("save" | "write", []) => {
+                let name = module.file_mod.name().clone();
+                if name.is_empty() {
+                    // 未命名的文件由MainView弹出另存为的选择框
+                    module.sendmsg(String::from("MainView"), String::from("saveas"));
+                    return Ok(());
                 }
+                module
+                    .file_mod
+                    .save()
+                    .map_err(|err| format!("Can't Save: {err}"))?;
+                module.sendmsg(String::from("Menu"), format!("File \"{name}\" Saved"));
+            }
+            ("write", [path]) => {
+                let path = self.path(module, path);
+                module
+                    .file_mod
+                    .write_to(&path)
+                    .map_err(|err| format!("Can't Write: {err}"))?;
+                let msg = format!("Written to \"{}\"", path.display());
+                module.sendmsg(String::from("Menu"), msg);
+            }
+            ("saveas", [path]) => {
+                let path = self.path(module, path);
+                let name = path.to_string_lossy().into_owned();
+                let file_mod = &mut module.file_mod;
+                file_mod
+                    .save_file(file_mod.curr_id(), Some(name))
+                    .map_err(|err| format!("Can't Save: {err}"))?;
+                let msg = format!("File \"{}\" Saved", file_mod.name());
+                module.sendmsg(String::from("Menu"), msg);
+            }
+            ("open", [path]) => {
+                let path = self.path(module, path);
+                if path.is_dir() {
+                    return Err(format!("Is a Directory: \"{}\"", path.display()));
+                }
+                let id = module
+                    .file_mod
+                    .open(&path)
+                    .map_err(|err| format!("Can't Open: {err}"))?;
+                module.sendmsg(String::from("MainView"), id.to_string());
+                module.push_op(Op::Shift(String::from("MainView")));
+            }
+            ("saveas" | "open" | "close" | "closeothers" | "closeall" | "reload", []) => {
+                module.sendmsg(String::from("MainView"), String::from(cmd.name));
+            }
+            ("cd", []) => {
+                let msg = module.file_mod.curr_dir().display().to_string();
+                module.sendmsg(String::from("Menu"), msg);
+            }
+            ("cd", [dir]) => {
+                let dir = self.path(module, dir);
+                if !dir.is_dir() {
+                    return Err(format!("Not a Directory: \"{}\"", dir.display()));
+                }
+                module
+                    .file_mod
+                    .change_dir(&dir)
+                    .map_err(|err| format!("Can't Change: {err}"))?;
+                let msg = format!("Change to \"{}\"", module.file_mod.curr_dir().display());
+                module.sendmsg(String::from("Menu"), msg);
+            }
+            ("set", []) => {
+                let settings = &module.settings;
+                let msg = Settings::OPTIONS
+                    .iter()
+                    .map(|option| format!("{option}={}", settings.get(option).unwrap()))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                module.sendmsg(String::from("Menu"), msg);
+            }
+            ("set", [option]) => {
+                let value = module
+                    .settings
+                    .get(option)
+                    .ok_or(format!("Unknown Option: `{option}`"))?;
+                module.sendmsg(String::from("Menu"), format!("{option}={value}"));
+            }
+            ("set", [option, value]) => {
+                module.settings.set(option, value)?;
+                module.sendmsg(String::from("Menu"), format!("{option}={value}"));
             }
-            other => {
-                module.sendmsg(String::from("Menu"), format!("Unkonwn Command: `{other}`"));
+            ("theme", []) => {
+                let msg = format!("Themes: {}", Theme::NAMES.join(", "));
+                module.sendmsg(String::from("Menu"), msg);
             }
+            ("theme", [name]) => {
+                let theme = Theme::from_name(name).ok_or(format!("Unknown Theme: `{name}`"))?;
+                module.settings.theme = theme;
+                module.push_op(Op::Init);
+                module.sendmsg(String::from("Menu"), format!("Theme \"{name}\""));
+            }
+            ("goto", [target]) => {
+                module.sendmsg(String::from("MainView"), format!("goto {target}"));
+            }
+            ("macro", [name] | [name, "lines"]) => self.play(module, name, "lines")?,
+            ("macro", [name, "matches"]) => self.play(module, name, "matches")?,
+            ("!", [shell]) if !shell.is_empty() => self.shell(module, shell)?,
+            (
+                "duplicate" | "deleteline" | "moveup" | "movedown" | "join" | "sort" | "sortdesc"
+                | "sortnum" | "unique" | "reverse" | "shuffle",
+                [],
+            ) => {
+                module.sendmsg(String::from("MainView"), String::from(cmd.name));
+            }
+            _ => return Err(cmd.usage()),
+        }
+        Ok(())
+    }
+
+    /// 相对于当前目录的路径，支持`~`
+    fn path(&self, module: &Module, path: &str) -> PathBuf {
+        let home = std::env::var_os("HOME").map(PathBuf::from);
+        match (path.strip_prefix('~'), home) {
+            (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+                home.join(rest.trim_start_matches('/'))
+            }
+            _ => module.file_mod.curr_dir().join(path),
         }
-        self.input.clear();
-        self.input_idx = 0;
+    }
+
+    fn play(&self, module: &mut Module, name: &str, target: &str) -> Result<(), String> {
+        if module.macros.get(name).is_none() {
+            return Err(format!("Unknown Macro \"{name}\""));
+        }
+        module.sendmsg(String::from("MainView"), format!("macro {name} {target}"));
+        Ok(())
+    }
+
+    /// 在当前目录执行shell命令
+    ///
+    /// 输出只有一行时显示在菜单中，有多行时在新文件中打开
+    fn shell(&self, module: &mut Module, shell: &str) -> Result<(), String> {
+        let output = process::Command::new("sh")
+            .arg("-c")
+            .arg(shell)
+            .current_dir(module.file_mod.curr_dir())
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|err| format!("Can't Run `{shell}`: {err}"))?;
+        let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+        text += &String::from_utf8_lossy(&output.stderr);
+        let text = text.trim_end();
+
+        match text.lines().count() {
+            0 => {
+                let msg = format!("`{shell}`: {}", output.status);
+                module.sendmsg(String::from("Menu"), msg);
+            }
+            1 => module.sendmsg(String::from("Menu"), text.to_string()),
+            _ => {
+                let id = module.file_mod.open_text(text.to_string());
+                module.sendmsg(String::from("MainView"), id.to_string());
+                module.push_op(Op::Shift(String::from("MainView")));
+            }
+        }
+        Ok(())
     }
 }
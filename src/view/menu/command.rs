@@ -0,0 +1,151 @@
+/// 菜单命令的解析
+///
+/// - 参数以空白分隔，可以用单引号或双引号包含空白，`\`转义下一个字符
+/// - 命令名可以缩写为唯一的前缀，另有`q`、`w`、`e`等常用的简写
+/// - 以`!`开头的输入作为shell命令，其余部分原样保留
+use std::fmt;
+
+/// 所有命令及其用法
+pub const COMMANDS: [(&str, &str); 27] = [
+    ("quit", "quit"),
+    ("save", "save"),
+    ("write", "write [path]"),
+    ("saveas", "saveas [path]"),
+    ("open", "open [path]"),
+    ("close", "close"),
+    ("closeothers", "closeothers"),
+    ("closeall", "closeall"),
+    ("reload", "reload"),
+    ("cd", "cd [dir]"),
+    ("set", "set [option] [value]"),
+    ("theme", "theme [name]"),
+    ("goto", "goto <line>[:<col>]"),
+    ("macro", "macro <name> [lines|matches]"),
+    ("duplicate", "duplicate"),
+    ("deleteline", "deleteline"),
+    ("moveup", "moveup"),
+    ("movedown", "movedown"),
+    ("join", "join"),
+    ("sort", "sort"),
+    ("sortdesc", "sortdesc"),
+    ("sortnum", "sortnum"),
+    ("unique", "unique"),
+    ("reverse", "reverse"),
+    ("shuffle", "shuffle"),
+    ("help", "help"),
+    ("!", "!<shell command>"),
+];
+
+/// 简写，优先于前缀匹配
+const ALIASES: [(&str, &str); 5] = [
+    ("q", "quit"),
+    ("w", "write"),
+    ("e", "open"),
+    ("o", "open"),
+    ("s", "save"),
+];
+
+/// 解析后的命令
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Command {
+    pub name: &'static str,
+    pub args: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    Empty,
+    Unknown(String),
+    Ambiguous(String, Vec<&'static str>),
+    Unclosed,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "Empty Command"),
+            ParseError::Unknown(name) => write!(f, "Unknown Command: `{name}`"),
+            ParseError::Ambiguous(name, names) => {
+                write!(f, "Ambiguous Command `{name}`: {}", names.join(", "))
+            }
+            ParseError::Unclosed => write!(f, "Unclosed Quote"),
+        }
+    }
+}
+
+impl Command {
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        let input = input.trim();
+        if let Some(shell) = input.strip_prefix('!') {
+            return Ok(Command {
+                name: "!",
+                args: vec![shell.trim().to_string()],
+            });
+        }
+        let mut words = split(input)?.into_iter();
+        let name = words.next().ok_or(ParseError::Empty)?;
+        Ok(Command {
+            name: resolve(&name)?,
+            args: words.collect(),
+        })
+    }
+
+    /// 命令的用法
+    pub fn usage(&self) -> String {
+        let usage = COMMANDS
+            .iter()
+            .find(|(name, _)| *name == self.name)
+            .map_or(self.name, |(_, usage)| usage);
+        format!("Usage: {usage}")
+    }
+}
+
+/// 把命令名或其缩写还原为完整的命令名
+pub fn resolve(name: &str) -> Result<&'static str, ParseError> {
+    if let Some((_, full)) = ALIASES.iter().find(|(alias, _)| *alias == name) {
+        return Ok(full);
+    }
+    if let Some((full, _)) = COMMANDS.iter().find(|(full, _)| *full == name) {
+        return Ok(full);
+    }
+    let names: Vec<&'static str> = COMMANDS
+        .iter()
+        .map(|(full, _)| *full)
+        .filter(|full| full.starts_with(name))
+        .collect();
+    match names[..] {
+        [] => Err(ParseError::Unknown(name.to_string())),
+        [full] => Ok(full),
+        _ => Err(ParseError::Ambiguous(name.to_string(), names)),
+    }
+}
+
+/// 按空白切分参数，处理引号与转义
+pub fn split(input: &str) -> Result<Vec<String>, ParseError> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quote: Option<char> = None;
+    let mut chars = input.chars();
+    while let Some(ch) = chars.next() {
+        match (quote, ch) {
+            (Some(q), ch) if ch == q => quote = None,
+            (Some('"') | None, '\\') => {
+                if let Some(next) = chars.next() {
+                    word.get_or_insert_with(String::new).push(next);
+                }
+            }
+            (Some(_), ch) => word.get_or_insert_with(String::new).push(ch),
+            (None, '"' | '\'') => {
+                quote = Some(ch);
+                word.get_or_insert_with(String::new);
+            }
+            (None, ch) if ch.is_whitespace() => words.extend(word.take()),
+            (None, ch) => word.get_or_insert_with(String::new).push(ch),
+        }
+    }
+    if quote.is_some() {
+        return Err(ParseError::Unclosed);
+    }
+    words.extend(word);
+    Ok(words)
+}
@@ -0,0 +1,179 @@
+/// 菜单命令的补全
+///
+/// 根据光标前的输入决定补全的内容：
+/// - 第一个词：命令名
+/// - `set`的第一个参数：设置项，第二个参数：开关类设置项为`on`/`off`，`sort`为排序方式
+/// - `theme`的参数：主题名
+/// - `write`、`saveas`、`open`、`cd`的参数：相对于当前目录的路径，`cd`只补全目录
+///
+/// 补全的词中的空白、引号与`\`会被转义
+use super::command::{self, COMMANDS};
+use crate::settings::{Settings, SortBy, Theme};
+use std::{fs, path::Path};
+
+/// 补全的结果
+#[derive(Debug, Clone, Default)]
+pub struct Completion {
+    /// 被替换的词在输入中的起始位置（字符下标）
+    pub start: usize,
+    /// 候选项，已转义，目录以`/`结尾
+    pub items: Vec<String>,
+}
+
+impl Completion {
+    /// 所有候选项的最长公共前缀
+    pub fn common_prefix(&self) -> String {
+        let mut items = self.items.iter();
+        let mut prefix: Vec<char> = match items.next() {
+            Some(first) => first.chars().collect(),
+            None => return String::new(),
+        };
+        for item in items {
+            let len = prefix
+                .iter()
+                .zip(item.chars())
+                .take_while(|(a, b)| *a == b)
+                .count();
+            prefix.truncate(len);
+        }
+        prefix.into_iter().collect()
+    }
+}
+
+/// 补全`input`的最后一个词，`dir`为相对路径的起点
+pub fn complete(input: &str, dir: &Path) -> Completion {
+    if input.trim_start().starts_with('!') {
+        return Completion::default();
+    }
+    let start = last_word(input);
+    let words = command::split(&input[..start]).unwrap_or_default();
+    let word = command::split(&input[start..])
+        .ok()
+        .and_then(|words| words.into_iter().next())
+        .unwrap_or_default();
+
+    let items = match words.first().map(|name| command::resolve(name)) {
+        None => {
+            let names = COMMANDS
+                .iter()
+                .map(|(name, _)| *name)
+                .filter(|name| *name != "!");
+            filter(names, &word)
+        }
+        Some(Ok("set")) if words.len() == 1 => filter(Settings::OPTIONS.into_iter(), &word),
+        Some(Ok("set")) if words.len() == 2 && words[1] == "sort" => {
+            filter(SortBy::NAMES.into_iter(), &word)
+        }
+        Some(Ok("set")) if words.len() == 2 && !matches!(&words[1][..], "indent" | "exclude") => {
+            filter(["on", "off"].into_iter(), &word)
+        }
+        Some(Ok("theme")) if words.len() == 1 => filter(Theme::NAMES.into_iter(), &word),
+        Some(Ok("write" | "saveas" | "open")) if words.len() == 1 => paths(&word, dir, false),
+        Some(Ok("cd")) if words.len() == 1 => paths(&word, dir, true),
+        _ => Vec::new(),
+    };
+    Completion {
+        start: input[..start].chars().count(),
+        items: items.iter().map(|item| escape(item)).collect(),
+    }
+}
+
+/// 最后一个词的起始位置，输入以空白结尾时为输入的长度
+fn last_word(input: &str) -> usize {
+    let mut start = 0;
+    let mut quote: Option<char> = None;
+    let mut is_escaped = false;
+    for (idx, ch) in input.char_indices() {
+        match (quote, ch) {
+            _ if is_escaped => is_escaped = false,
+            (Some('"') | None, '\\') => is_escaped = true,
+            (Some(q), ch) if ch == q => quote = None,
+            (None, '"' | '\'') => quote = Some(ch),
+            (None, ch) if ch.is_whitespace() => start = idx + ch.len_utf8(),
+            _ => (),
+        }
+    }
+    start
+}
+
+fn filter<'a>(names: impl Iterator<Item = &'a str>, word: &str) -> Vec<String> {
+    names
+        .filter(|name| name.starts_with(word))
+        .map(String::from)
+        .collect()
+}
+
+/// 以`word`开头的路径，以`.`开头的文件只在`word`的文件名部分也以`.`开头时补全
+fn paths(word: &str, dir: &Path, dir_only: bool) -> Vec<String> {
+    let (parent, name) = match word.rfind('/') {
+        Some(idx) => word.split_at(idx + 1),
+        None => ("", word),
+    };
+    let home = std::env::var_os("HOME").unwrap_or_default();
+    let base = match parent.strip_prefix('~') {
+        Some(rest) if rest.starts_with('/') => Path::new(&home).join(&rest[1..]),
+        _ => dir.join(parent),
+    };
+    let entries = match fs::read_dir(base) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut items: Vec<String> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            if !file_name.starts_with(name)
+                || (file_name.starts_with('.') && !name.starts_with('.'))
+            {
+                return None;
+            }
+            // 跟随符号链接判断是否为目录
+            let is_dir = entry.path().is_dir();
+            match (is_dir, dir_only) {
+                (true, _) => Some(format!("{parent}{file_name}/")),
+                (false, false) => Some(format!("{parent}{file_name}")),
+                (false, true) => None,
+            }
+        })
+        .collect();
+    items.sort();
+    items
+}
+
+fn escape(word: &str) -> String {
+    let mut escaped = String::with_capacity(word.len());
+    for ch in word.chars() {
+        if ch.is_whitespace() || matches!(ch, '"' | '\'' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn last_word_after_whitespace() {
+        assert_eq!(last_word("open src/ma"), 5);
+        assert_eq!(last_word("open "), 5);
+        assert_eq!(last_word("open"), 0);
+        assert_eq!(last_word(""), 0);
+    }
+
+    #[test]
+    fn last_word_skips_quoted_and_escaped_spaces() {
+        assert_eq!(last_word("open \"a b"), 5);
+        assert_eq!(last_word("open 'a b' c"), 11);
+        assert_eq!(last_word("open a\\ b"), 5);
+        assert_eq!(last_word("open 'a\\' b"), 10);
+    }
+
+    #[test]
+    fn last_word_counts_bytes() {
+        assert_eq!(last_word("cd 目录 子"), 10);
+    }
+}
@@ -1,18 +1,65 @@
-/// MsgBox提供一个能输入的弹窗
+/// MsgBox提供各种弹窗
 ///
-/// 通过<Enter>来提交输入的内容，会返回输入的内容
-/// 可以指定返回的类型
+/// - `wait`/`ask`：输入框，通过<Enter>来提交输入的内容，可以指定返回的类型
+/// - `confirm`：确认框，按<y>/<n>/<c>或用方向键选择后按<Enter>，
+///   危险的操作应通过`default_answer`使<Enter>默认为`No`
+/// - `choose`：从列表中选择一项
+/// - `form`：有多个输入框的表单，<Tab>/<Up>/<Down>切换输入框，在最后一个输入框按<Enter>提交
+/// - `show`：只读的消息框，用于显示错误等信息
+///
+/// 除消息框外，按<Esc>取消，调用者可以把取消与空的输入区分开
+/// 输入的编辑方式见`LineEdit`
+use super::lineedit::LineEdit;
 use crate::prelude::*;
 use getch_rs::Key;
 use std::str::FromStr;
 
+/// 列表最多显示的行数
+const LIST_ROWS: usize = 10;
+/// 确认框的按钮
+const BUTTONS: [&str; 3] = ["[Y]es", "[N]o", "[C]ancel"];
+
+/// 确认框的回答，顺序与`BUTTONS`相同
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Answer {
+    Yes,
+    No,
+    Cancel,
+}
+
+/// 弹窗的种类
+#[derive(Debug, Clone, Default)]
+enum Kind {
+    #[default]
+    Input,
+    /// 选中的按钮
+    Confirm(usize),
+    List {
+        items: Vec<String>,
+        selected: usize,
+        scroll: usize,
+    },
+    /// `(标签, 输入框)`与聚焦的输入框
+    Form {
+        fields: Vec<(String, LineEdit)>,
+        focus: usize,
+    },
+    Message(Vec<String>),
+}
+
 #[view("MsgBox")]
 #[start=(1, 1)]
 #[end=(2, 2)]
 pub struct MsgBox {
     title: String,
-    input: String,
-    input_idx: usize,
+    input: LineEdit,
+    kind: Kind,
+    is_cancelled: bool,
+    /// 确认框初始选中的按钮
+    default_answer: usize,
+    /// 是否根据内容自动决定位置
+    is_auto: bool,
+    select_clr: Color,
 }
 
 impl View for MsgBox {
@@ -20,81 +67,131 @@ impl View for MsgBox {
         let settings = &module.settings;
         self.fcolor = settings.theme.normal_fclr.clone();
         self.bcolor = settings.theme.normal_bclr.clone();
+        self.select_clr = settings.theme.bright_black.clone();
     }
-    fn update(&mut self, _: &mut Module) {}
-    fn matchar(&mut self, module: &mut Module, key: getch_rs::Key) {
-        let term = &module.term;
-        let max = self.get_end(term).0 - self.get_start(term).0 - 2;
-        match key {
-            Key::Esc => {
-                self.input = String::new();
-                self.lock = false;
+    fn update(&mut self, module: &mut Module) {
+        let width = self.inner_width(&module.term);
+        self.input.set_width(width);
+        if let Kind::Form { fields, .. } = &mut self.kind {
+            let label_width = label_width(fields);
+            for (_, input) in fields.iter_mut() {
+                input.set_width(width.saturating_sub(label_width + 3));
             }
-            Key::Char('\r') => {
-                self.lock = false;
-            }
-            Key::Char(ch) => {
-                if self.input.len() < max as usize {
-                    self.input.insert(self.input_idx, ch);
-                    self.input_idx += 1;
+        }
+    }
+    fn matchar(&mut self, _: &mut Module, key: getch_rs::Key) {
+        // 终端关闭时同样取消
+        if let Key::Esc | Key::EOF = key {
+            self.input.clear();
+            self.is_cancelled = true;
+            self.lock = false;
+            return;
+        }
+        match &mut self.kind {
+            Kind::Input => match key {
+                Key::Char('\r') => self.lock = false,
+                key => {
+                    self.input.handle(&key);
                 }
-            }
-            Key::Delete => {
-                if !self.input.is_empty() {
-                    self.input_idx -= 1;
-                    self.input.remove(self.input_idx);
+            },
+            Kind::Confirm(selected) => match key {
+                Key::Char('y' | 'Y') => self.answer(0),
+                Key::Char('n' | 'N') => self.answer(1),
+                Key::Char('c' | 'C') => self.answer(2),
+                Key::Char('\r') => self.lock = false,
+                Key::Left | Key::BackTab => {
+                    *selected = (*selected + BUTTONS.len() - 1) % BUTTONS.len()
                 }
-            }
-
-            Key::Left => {
-                if self.input_idx > 0 {
-                    self.input_idx -= 1;
+                Key::Right | Key::Char('\t') => *selected = (*selected + 1) % BUTTONS.len(),
+                _ => (),
+            },
+            Kind::List {
+                items,
+                selected,
+                scroll,
+            } => {
+                let last = items.len().saturating_sub(1);
+                *selected = match key {
+                    Key::Char('\r') => {
+                        self.lock = false;
+                        return;
+                    }
+                    Key::Up | Key::Ctrl('p') => selected.saturating_sub(1),
+                    Key::Down | Key::Ctrl('n') => (*selected + 1).min(last),
+                    Key::PageUp => selected.saturating_sub(LIST_ROWS),
+                    Key::PageDown => (*selected + LIST_ROWS).min(last),
+                    Key::Home => 0,
+                    Key::End => last,
+                    _ => *selected,
+                };
+                if *selected < *scroll {
+                    *scroll = *selected;
+                } else if *selected >= *scroll + LIST_ROWS {
+                    *scroll = *selected + 1 - LIST_ROWS;
                 }
             }
-
-            Key::Right => {
-                if self.input_idx < self.input.len() {
-                    self.input_idx += 1;
+            Kind::Form { fields, focus } => match key {
+                Key::Char('\r') if *focus + 1 == fields.len() => self.lock = false,
+                Key::Char('\r' | '\t') | Key::Down => *focus = (*focus + 1) % fields.len(),
+                Key::BackTab | Key::Up => *focus = (*focus + fields.len() - 1) % fields.len(),
+                key => {
+                    fields[*focus].1.handle(&key);
+                }
+            },
+            Kind::Message(_) => {
+                if let Key::Char('\r' | ' ' | 'q') = key {
+                    self.lock = false;
                 }
             }
-
-            _ => (),
         }
     }
 
     fn set_cursor(&self, module: &mut Module) {
         let term = &module.term;
         let (x, y) = self.get_start(term);
-        let csr_x = x + 1 + self.input_idx as u16;
-        Cursor::set_csr(csr_x, y + 1);
+        let width = self.inner_width(term);
+        let (col, row) = match &self.kind {
+            Kind::Input => (self.input.cursor_col(), 0),
+            Kind::Confirm(selected) => {
+                let offset = (width.saturating_sub(buttons_width())) / 2;
+                let before: usize = BUTTONS[..*selected]
+                    .iter()
+                    .map(|button| button.len() + 2)
+                    .sum();
+                (offset + before + 1, 0)
+            }
+            Kind::List {
+                selected, scroll, ..
+            } => (0, selected - scroll),
+            Kind::Form { fields, focus } => {
+                let col = label_width(fields) + 3 + fields[*focus].1.cursor_col();
+                (col, *focus)
+            }
+            Kind::Message(lines) => (width.saturating_sub(6) / 2 + 1, lines.len()),
+        };
+        Cursor::set_csr(x + 1 + col as u16, y + 1 + row as u16);
     }
 
     fn draw(&self, module: &mut Module) -> io::Result<()> {
         let term = &module.term;
         self.refresh(term);
         let (x, y) = self.get_start(term);
-        let (x_e, y_e) = self.get_end(term);
-        let max_x = (x_e - x) as usize;
-        let mut max_y = (y_e - y) as usize;
+        let (_, y_e) = self.get_end(term);
+        let width = self.inner_width(term);
+        let head = format!("{}{}", self.fcolor.fclr_head(), self.bcolor.bclr_head());
+        let title: String = self.title.chars().take(width).collect();
         Cursor::set_csr(x, y);
-        print!("{}{}", self.fcolor.fclr_head(), self.bcolor.bclr_head());
-        println!("╭{:─^width$}╮", self.title, width = max_x - 2);
-
-        Cursor::csr_setcol(x);
-        print!("{}{}", self.fcolor.fclr_head(), self.bcolor.bclr_head());
-        println!("│{:<width$}│", self.input, width = max_x - 2);
-        max_y -= 1;
+        println!("{head}╭{:─^width$}╮", title);
 
-        while max_y > 2 {
+        let mut lines = self.body(width, &head);
+        lines.resize(((y_e - y) as usize).saturating_sub(2), " ".repeat(width));
+        for line in lines {
             Cursor::csr_setcol(x);
-            print!("{}{}", self.fcolor.fclr_head(), self.bcolor.bclr_head());
-            println!("│{}│", " ".repeat(max_x - 2));
-            max_y -= 1;
+            println!("{head}│{line}{head}│");
         }
 
         Cursor::csr_setcol(x);
-        print!("{}{}", self.fcolor.fclr_head(), self.bcolor.bclr_head());
-        println!("╰{}╯", "─".repeat(max_x - 2));
+        println!("{head}╰{}╯", "─".repeat(width));
         io::stdout().flush()?;
 
         Ok(())
@@ -113,23 +210,15 @@ impl MsgBox {
 
         self.start = (x_s, y_s);
         self.end = (x_e, y_e);
+        self.is_auto = false;
 
         self
     }
 
+    /// 位于屏幕中央，大小随弹窗的内容而定
     pub fn default_pos(&mut self, module: &mut Module) -> &mut Self {
-        let (height, width) = (module.term.height as i16, module.term.width as i16);
-        let length = (self.title.len() / 2) as i16 + 10;
-        let start = (
-            Pos::try_from(width / 2 - length).unwrap(),
-            Pos::try_from(height / 2 - 1).unwrap(),
-        );
-        let end = (
-            Pos::try_from(width / 2 + length).unwrap(),
-            Pos::try_from(height / 2 + 2).unwrap(),
-        );
-        self.start = start;
-        self.end = end;
+        self.is_auto = true;
+        self.layout(module);
         self
     }
 
@@ -138,12 +227,99 @@ impl MsgBox {
         self
     }
 
+    /// 输入框的初始内容
+    pub fn input(&mut self, text: &str) -> &mut Self {
+        self.input.set_text(text);
+        self
+    }
+
+    /// 确认框初始选中的按钮，不设置时为`Yes`
+    pub fn default_answer(&mut self, answer: Answer) -> &mut Self {
+        self.default_answer = answer as usize;
+        self
+    }
+
+    /// 等待输入，取消时按空的输入解析
     pub fn wait<T>(&mut self, module: &mut Module) -> Result<T, <T as FromStr>::Err>
     where
         T: FromStr,
     {
-        let key_events = module.key_channel();
+        self.run(module);
+        self.input.text().parse::<T>()
+    }
+
+    /// 等待输入，取消时返回`None`
+    pub fn ask<T>(&mut self, module: &mut Module) -> Option<Result<T, <T as FromStr>::Err>>
+    where
+        T: FromStr,
+    {
+        let input = self.wait::<T>(module);
+        (!self.is_cancelled).then_some(input)
+    }
+
+    /// 等待回答，<Esc>视作取消
+    pub fn confirm(&mut self, module: &mut Module) -> Answer {
+        self.kind = Kind::Confirm(self.default_answer);
+        self.run(module);
+        match self.kind {
+            _ if self.is_cancelled => Answer::Cancel,
+            Kind::Confirm(0) => Answer::Yes,
+            Kind::Confirm(1) => Answer::No,
+            _ => Answer::Cancel,
+        }
+    }
+
+    /// 从`items`中选择一项，返回其下标，取消或列表为空时返回`None`
+    pub fn choose(&mut self, module: &mut Module, items: Vec<String>) -> Option<usize> {
+        if items.is_empty() {
+            return None;
+        }
+        self.kind = Kind::List {
+            items,
+            selected: 0,
+            scroll: 0,
+        };
+        self.run(module);
+        match self.kind {
+            _ if self.is_cancelled => None,
+            Kind::List { selected, .. } => Some(selected),
+            _ => None,
+        }
+    }
+
+    /// 填写表单，`fields`为`(标签, 初始内容)`，返回各输入框的内容，取消时返回`None`
+    pub fn form(&mut self, module: &mut Module, fields: &[(&str, &str)]) -> Option<Vec<String>> {
+        let fields = fields
+            .iter()
+            .map(|(label, text)| {
+                let mut input = LineEdit::default();
+                input.set_text(text);
+                (label.to_string(), input)
+            })
+            .collect();
+        self.kind = Kind::Form { fields, focus: 0 };
+        self.run(module);
+        match &self.kind {
+            _ if self.is_cancelled => None,
+            Kind::Form { fields, .. } => {
+                Some(fields.iter().map(|(_, input)| input.text()).collect())
+            }
+            _ => None,
+        }
+    }
+
+    /// 显示消息，按<Enter>、<Space>或<Esc>关闭
+    pub fn show(&mut self, module: &mut Module, text: &str) {
+        self.kind = Kind::Message(text.lines().map(String::from).collect());
+        self.run(module);
+    }
+
+    fn run(&mut self, module: &mut Module) {
         self.lock = true;
+        self.is_cancelled = false;
+        if self.is_auto {
+            self.layout(module);
+        }
         self.init(module);
         self.update(module);
         self.draw(module).unwrap();
@@ -151,7 +327,7 @@ impl MsgBox {
         io::stdout().flush().unwrap();
 
         while self.lock {
-            let key = key_events.recv().unwrap();
+            let key = module.next_key();
             self.matchar(module, key);
 
             self.update(module);
@@ -159,7 +335,122 @@ impl MsgBox {
             self.set_cursor(module);
             io::stdout().flush().unwrap();
         }
+    }
 
-        self.input.parse::<T>()
+    fn answer(&mut self, selected: usize) {
+        self.kind = Kind::Confirm(selected);
+        self.lock = false;
     }
+
+    /// 根据内容决定大小，并放在屏幕中央
+    fn layout(&mut self, module: &Module) {
+        let title = self.title.chars().count();
+        let (width, height) = match &self.kind {
+            Kind::Input => (title + 20, 1),
+            Kind::Confirm(_) => (title.max(buttons_width()) + 8, 1),
+            Kind::List { items, .. } => {
+                let widest = items.iter().map(|item| item.chars().count()).max();
+                (
+                    title.max(widest.unwrap_or(0)) + 8,
+                    items.len().min(LIST_ROWS),
+                )
+            }
+            Kind::Form { fields, .. } => (title.max(label_width(fields) + 30) + 8, fields.len()),
+            Kind::Message(lines) => {
+                let widest = lines.iter().map(|line| line.chars().count()).max();
+                (title.max(widest.unwrap_or(0)) + 8, lines.len() + 1)
+            }
+        };
+
+        let (term_width, term_height) = (module.term.width as i16, module.term.height as i16);
+        let half = (width as i16 / 2 + 1).min(term_width / 2 - 1);
+        let height = (height as i16 + 2).min(term_height - 2);
+        let top = term_height / 2 - height / 2;
+        self.start = (
+            Pos::try_from(term_width / 2 - half).unwrap(),
+            Pos::try_from(top).unwrap(),
+        );
+        self.end = (
+            Pos::try_from(term_width / 2 + half).unwrap(),
+            Pos::try_from(top + height).unwrap(),
+        );
+    }
+
+    /// 边框以内的宽度
+    fn inner_width(&self, term: &Term) -> usize {
+        let (x, _) = self.get_start(term);
+        let (x_e, _) = self.get_end(term);
+        ((x_e - x) as usize).saturating_sub(2)
+    }
+
+    /// 边框以内的各行，已补齐到`width`
+    fn body(&self, width: usize, head: &str) -> Vec<String> {
+        let select = self.select_clr.bclr_head();
+        match &self.kind {
+            Kind::Input => vec![self.input.render()],
+            Kind::Confirm(selected) => {
+                let offset = width.saturating_sub(buttons_width()) / 2;
+                let mut line = " ".repeat(offset);
+                for (idx, button) in BUTTONS.iter().enumerate() {
+                    let bclr = if idx == *selected { &select } else { head };
+                    line += &format!("{bclr} {button} {head}");
+                }
+                line += &" ".repeat(width.saturating_sub(offset + buttons_width()));
+                vec![line]
+            }
+            Kind::List {
+                items,
+                selected,
+                scroll,
+            } => items
+                .iter()
+                .enumerate()
+                .skip(*scroll)
+                .take(LIST_ROWS)
+                .map(|(idx, item)| {
+                    let bclr = if idx == *selected { &select } else { head };
+                    let item: String = item.chars().take(width.saturating_sub(2)).collect();
+                    format!("{bclr} {item:<w$} {head}", w = width.saturating_sub(2))
+                })
+                .collect(),
+            Kind::Form { fields, .. } => {
+                let label_width = label_width(fields);
+                fields
+                    .iter()
+                    .map(|(label, input)| format!(" {label:>label_width$}: {}", input.render()))
+                    .collect()
+            }
+            Kind::Message(lines) => {
+                let mut body: Vec<String> = lines
+                    .iter()
+                    .map(|line| {
+                        let line: String = line.chars().take(width.saturating_sub(2)).collect();
+                        format!(" {line:<w$} ", w = width.saturating_sub(2))
+                    })
+                    .collect();
+                let offset = width.saturating_sub(6) / 2;
+                body.push(format!(
+                    "{}{select}[ OK ]{head}{}",
+                    " ".repeat(offset),
+                    " ".repeat(width.saturating_sub(offset + 6))
+                ));
+                body
+            }
+        }
+    }
+}
+
+/// 按钮连同两侧空格的总宽度
+#[inline]
+fn buttons_width() -> usize {
+    BUTTONS.iter().map(|button| button.len() + 2).sum()
+}
+
+#[inline]
+fn label_width(fields: &[(String, LineEdit)]) -> usize {
+    fields
+        .iter()
+        .map(|(label, _)| label.chars().count())
+        .max()
+        .unwrap_or(0)
 }
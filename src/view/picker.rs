@@ -0,0 +1,342 @@
+/// Picker提供一个带模糊过滤的列表弹窗
+///
+/// 输入的内容会实时过滤并排序候选项，匹配的字符会被高亮
+/// 通过<Up>/<Down>（或<Ctrl+p>/<Ctrl+n>）与<PageUp>/<PageDown>选择，<Enter>确认，<Esc>取消
+/// 输入的编辑方式见`LineEdit`
+///
+/// 候选项可以通过管道陆续加入，读取大量文件时也能同时输入
+use super::lineedit::{Edited, LineEdit};
+use crate::prelude::*;
+use crossbeam_channel::{Receiver, TryRecvError};
+use getch_rs::Key;
+use std::time::Duration;
+
+mod fuzzy;
+
+use fuzzy::Query;
+
+/// 等待陆续加入的候选项时，检查按键的间隔
+const TICK: Duration = Duration::from_millis(30);
+
+/// 候选项
+#[derive(Debug, Clone, Default)]
+pub struct Item {
+    /// 用于匹配与显示的文本
+    pub label: String,
+    /// 显示在右侧的说明
+    pub detail: String,
+    /// 附加的分数，如最近使用的文件
+    pub bonus: i64,
+}
+
+impl Item {
+    pub fn new(label: String, detail: String, bonus: i64) -> Self {
+        Item {
+            label,
+            detail,
+            bonus,
+        }
+    }
+}
+
+#[view("Picker")]
+#[start=(1, 1)]
+#[end=(2, 2)]
+pub struct Picker {
+    title: String,
+    input: LineEdit,
+    items: Vec<Item>,
+    /// 匹配的候选项`(下标, 分数)`，按分数从高到低排列
+    matches: Vec<(usize, i64)>,
+    /// `matches`对应的输入
+    query: String,
+    /// 陆续加入的候选项
+    source: Option<Receiver<Vec<Item>>>,
+    selected: usize,
+    scroll: usize,
+    /// 确认的候选项，取消时为`None`
+    chosen: Option<usize>,
+    match_clr: Color,
+    select_clr: Color,
+    detail_clr: Color,
+}
+
+impl View for Picker {
+    fn init(&mut self, module: &mut Module) {
+        let settings = &module.settings;
+        self.fcolor = settings.theme.normal_fclr.clone();
+        self.bcolor = settings.theme.normal_bclr.clone();
+        self.match_clr = settings.theme.yellow.clone();
+        self.select_clr = settings.theme.bright_black.clone();
+        self.detail_clr = settings.theme.weak_fclr.clone();
+    }
+    fn update(&mut self, _: &mut Module) {}
+    fn matchar(&mut self, module: &mut Module, key: Key) {
+        let rows = self.rows(&module.term);
+        match key {
+            Key::Esc | Key::EOF => {
+                self.chosen = None;
+                self.lock = false;
+            }
+            Key::Char('\r') => {
+                self.chosen = self.matches.get(self.selected).map(|(idx, _)| *idx);
+                self.lock = false;
+            }
+            Key::Up | Key::Ctrl('p') => self.select(self.selected.saturating_sub(1), rows),
+            Key::Down | Key::Ctrl('n') => self.select(self.selected + 1, rows),
+            Key::PageUp => self.select(self.selected.saturating_sub(rows), rows),
+            Key::PageDown => self.select(self.selected + rows, rows),
+            key => {
+                if self.input.handle(&key) == Edited::Text {
+                    self.filter();
+                }
+            }
+        }
+    }
+
+    fn set_cursor(&self, module: &mut Module) {
+        let term = &module.term;
+        let (x, y) = self.get_start(term);
+        let csr_x = x + 3 + self.input.cursor_col() as u16;
+        Cursor::set_csr(csr_x, y + 1);
+    }
+
+    fn draw(&self, module: &mut Module) -> io::Result<()> {
+        let term = &module.term;
+        self.refresh(term);
+        let (x, y) = self.get_start(term);
+        let width = self.inner_width(term);
+        let head = format!("{}{}", self.fcolor.fclr_head(), self.bcolor.bclr_head());
+
+        let title = format!(" {} ", self.title);
+        let loading = if self.source.is_some() { "…" } else { "" };
+        let count = format!(" {}/{}{loading} ", self.matches.len(), self.items.len());
+        let fill = width.saturating_sub(title.chars().count() + count.chars().count());
+        Cursor::set_csr(x, y);
+        println!("{head}╭{title}{}{count}╮", "─".repeat(fill));
+
+        Cursor::csr_setcol(x);
+        println!("{head}│> {}│", self.input.render());
+
+        Cursor::csr_setcol(x);
+        println!("{head}├{}┤", "─".repeat(width));
+
+        let query = Query::new(&self.query);
+        for row in 0..self.rows(term) {
+            let line = match self.matches.get(self.scroll + row) {
+                Some((idx, _)) => {
+                    let is_selected = self.scroll + row == self.selected;
+                    self.render(&self.items[*idx], &query, width, is_selected)
+                }
+                None => " ".repeat(width),
+            };
+            Cursor::csr_setcol(x);
+            println!("{head}│{line}{head}│");
+        }
+
+        Cursor::csr_setcol(x);
+        print!("{head}╰{}╯", "─".repeat(width));
+        io::stdout().flush()?;
+        Ok(())
+    }
+}
+
+#[allow(unused)]
+impl Picker {
+    pub fn pos(&mut self, start: (i16, i16), end: (i16, i16)) -> &mut Self {
+        let (x_s, y_s) = start;
+        let (x_e, y_e) = end;
+        self.start = (Pos::try_from(x_s).unwrap(), Pos::try_from(y_s).unwrap());
+        self.end = (Pos::try_from(x_e).unwrap(), Pos::try_from(y_e).unwrap());
+        self
+    }
+
+    /// 占据屏幕中部约三分之二的区域
+    pub fn default_pos(&mut self, module: &mut Module) -> &mut Self {
+        let (height, width) = (module.term.height as i16, module.term.width as i16);
+        let (margin_x, margin_y) = ((width / 6).max(1), (height / 6).max(1));
+        self.pos((margin_x, margin_y), (-margin_x, -margin_y))
+    }
+
+    pub fn title(&mut self, content: &str) -> &mut Self {
+        self.title = content.into();
+        self
+    }
+
+    pub fn items(&mut self, items: Vec<Item>) -> &mut Self {
+        self.items = items;
+        self
+    }
+
+    /// 在弹窗打开期间从`source`陆续读取候选项，直到发送端关闭
+    pub fn source(&mut self, source: Receiver<Vec<Item>>) -> &mut Self {
+        self.source = Some(source);
+        self
+    }
+
+    /// 等待选择，返回选中的候选项，取消时返回`None`
+    pub fn wait(&mut self, module: &mut Module) -> Option<Item> {
+        self.lock = true;
+        self.init(module);
+        let width = self.inner_width(&module.term);
+        self.input.set_width(width.saturating_sub(2));
+        self.filter();
+        self.draw(module).unwrap();
+        self.set_cursor(module);
+        io::stdout().flush().unwrap();
+
+        while self.lock {
+            let key = if self.source.is_some() {
+                module.next_key_timeout(TICK)
+            } else {
+                Some(module.next_key())
+            };
+            let mut is_changed = self.poll();
+            if let Some(key) = key {
+                self.matchar(module, key);
+                is_changed = true;
+            }
+
+            if is_changed && self.lock {
+                self.draw(module).unwrap();
+                self.set_cursor(module);
+                io::stdout().flush().unwrap();
+            }
+        }
+
+        self.chosen.map(|idx| self.items[idx].clone())
+    }
+
+    /// 边框以内的宽度
+    fn inner_width(&self, term: &Term) -> usize {
+        let (x, _) = self.get_start(term);
+        let (x_e, _) = self.get_end(term);
+        ((x_e - x) as usize).saturating_sub(2)
+    }
+
+    /// 列表可以显示的行数
+    fn rows(&self, term: &Term) -> usize {
+        let (_, y) = self.get_start(term);
+        let (_, y_e) = self.get_end(term);
+        ((y_e - y) as usize).saturating_sub(4)
+    }
+
+    fn select(&mut self, selected: usize, rows: usize) {
+        self.selected = selected.min(self.matches.len().saturating_sub(1));
+        if self.selected < self.scroll {
+            self.scroll = self.selected;
+        } else if rows > 0 && self.selected >= self.scroll + rows {
+            self.scroll = self.selected + 1 - rows;
+        }
+    }
+
+    /// 根据输入重新过滤
+    ///
+    /// 输入只是在原来的基础上增加了字符时，只需在已匹配的候选项中查找
+    fn filter(&mut self) {
+        let input = self.input.text();
+        let query = Query::new(&input);
+        let candidates: Vec<usize> = if !self.query.is_empty() && input.starts_with(&self.query) {
+            self.matches.iter().map(|(idx, _)| *idx).collect()
+        } else {
+            (0..self.items.len()).collect()
+        };
+        let items = &self.items;
+        self.matches = candidates
+            .into_iter()
+            .filter_map(|idx| {
+                let item = &items[idx];
+                query
+                    .score(&item.label)
+                    .map(|score| (idx, score + item.bonus))
+            })
+            .collect();
+        self.sort();
+        self.query = input;
+        self.selected = 0;
+        self.scroll = 0;
+    }
+
+    /// 读取陆续加入的候选项，返回是否有变化
+    fn poll(&mut self) -> bool {
+        let source = match &self.source {
+            Some(source) => source,
+            None => return false,
+        };
+        let mut batch = Vec::new();
+        let is_closed = loop {
+            match source.try_recv() {
+                Ok(mut items) => batch.append(&mut items),
+                Err(TryRecvError::Empty) => break false,
+                Err(TryRecvError::Disconnected) => break true,
+            }
+        };
+        if is_closed {
+            self.source = None;
+        }
+        if batch.is_empty() {
+            return is_closed;
+        }
+
+        let query = Query::new(&self.query);
+        let start = self.items.len();
+        self.items.append(&mut batch);
+        for (idx, item) in self.items.iter().enumerate().skip(start) {
+            if let Some(score) = query.score(&item.label) {
+                self.matches.push((idx, score + item.bonus));
+            }
+        }
+        self.sort();
+        true
+    }
+
+    /// 按分数从高到低排列，同分时较短的靠前
+    fn sort(&mut self) {
+        let items = &self.items;
+        self.matches.sort_unstable_by(|(a, a_score), (b, b_score)| {
+            b_score
+                .cmp(a_score)
+                .then_with(|| items[*a].label.len().cmp(&items[*b].label.len()))
+                .then_with(|| a.cmp(b))
+        });
+    }
+
+    /// 绘制一个候选项，过长时省略开头的部分
+    fn render(&self, item: &Item, query: &Query, width: usize, is_selected: bool) -> String {
+        let bclr = if is_selected {
+            &self.select_clr
+        } else {
+            &self.bcolor
+        };
+        let positions = query
+            .find(&item.label)
+            .map(|(_, pos)| pos)
+            .unwrap_or_default();
+        let detail_len = item.detail.chars().count();
+        let avail = width.saturating_sub(detail_len + 3);
+
+        let chars: Vec<char> = item.label.chars().collect();
+        let mut skip = chars.len().saturating_sub(avail);
+        let mut line = format!("{} ", bclr.bclr_head());
+        let mut shown = 0;
+        if skip > 0 {
+            line += &"…".fclr_head(&self.detail_clr);
+            skip += 1;
+            shown += 1;
+        }
+        for (idx, ch) in chars.iter().enumerate().skip(skip) {
+            let clr = if positions.contains(&idx) {
+                &self.match_clr
+            } else {
+                &self.fcolor
+            };
+            line += &ch.to_string().fclr_head(clr);
+            shown += 1;
+        }
+
+        let fill = width.saturating_sub(shown + detail_len + 2);
+        line += &" ".repeat(fill);
+        line += &item.detail.fclr_head(&self.detail_clr);
+        line + " "
+    }
+}
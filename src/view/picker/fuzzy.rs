@@ -0,0 +1,148 @@
+/// 模糊匹配
+///
+/// 查询中的字符按顺序出现在文本中即为匹配，查询中含有大写字母时区分大小写
+/// 分数越高越好：连续匹配、单词边界（`/`、`_`、`-`、`.`、空格之后或驼峰处）
+/// 与文件名部分的匹配会加分，跳过的字符会扣分
+use std::iter;
+
+/// 每个匹配字符的基础分
+const SCORE_MATCH: i64 = 16;
+/// 与上一个匹配字符相邻
+const BONUS_CONSECUTIVE: i64 = 12;
+/// 位于单词边界
+const BONUS_BOUNDARY: i64 = 10;
+/// 全部位于最后一个`/`之后
+const BONUS_BASENAME: i64 = 20;
+/// 单个间隔的最大扣分
+const PENALTY_GAP_MAX: i64 = 3;
+
+/// 预处理后的查询
+#[derive(Debug, Clone, Default)]
+pub struct Query {
+    chars: Vec<char>,
+    is_case: bool,
+}
+
+impl Query {
+    pub fn new(input: &str) -> Self {
+        Query {
+            chars: input.chars().filter(|ch| !ch.is_whitespace()).collect(),
+            is_case: input.chars().any(char::is_uppercase),
+        }
+    }
+
+    #[inline]
+    fn eq(&self, pattern: char, ch: char) -> bool {
+        if self.is_case {
+            pattern == ch
+        } else {
+            pattern == ch || ch.to_lowercase().eq(iter::once(pattern))
+        }
+    }
+
+    /// 快速判断是否匹配，不计算分数
+    pub fn is_match(&self, text: &str) -> bool {
+        let mut pattern = self.chars.iter().peekable();
+        for ch in text.chars() {
+            match pattern.peek() {
+                Some(&&p) if self.eq(p, ch) => {
+                    pattern.next();
+                }
+                Some(_) => (),
+                None => break,
+            }
+        }
+        pattern.peek().is_none()
+    }
+
+    /// 匹配的分数，不匹配时返回`None`
+    pub fn score(&self, text: &str) -> Option<i64> {
+        self.find(text).map(|(score, _)| score)
+    }
+
+    /// 匹配的分数与匹配字符的位置（字符下标）
+    pub fn find(&self, text: &str) -> Option<(i64, Vec<usize>)> {
+        if !self.is_match(text) {
+            return None;
+        }
+        let text: Vec<char> = text.chars().collect();
+        if self.chars.is_empty() {
+            return Some((0, Vec::new()));
+        }
+
+        // 先向前找到第一个完整匹配的结尾，再从结尾向后收紧起点
+        let mut end = 0;
+        let mut pattern = 0;
+        for (idx, &ch) in text.iter().enumerate() {
+            if self.eq(self.chars[pattern], ch) {
+                pattern += 1;
+                if pattern == self.chars.len() {
+                    end = idx;
+                    break;
+                }
+            }
+        }
+        let mut start = end;
+        let mut pattern = self.chars.len();
+        for idx in (0..=end).rev() {
+            if self.eq(self.chars[pattern - 1], text[idx]) {
+                pattern -= 1;
+                if pattern == 0 {
+                    start = idx;
+                    break;
+                }
+            }
+        }
+
+        let mut positions = Vec::with_capacity(self.chars.len());
+        let mut pattern = self.chars.iter().peekable();
+        for (idx, &ch) in text.iter().enumerate().take(end + 1).skip(start) {
+            if let Some(&&p) = pattern.peek() {
+                if self.eq(p, ch) {
+                    positions.push(idx);
+                    pattern.next();
+                }
+            }
+        }
+
+        Some((calc_score(&text, &positions), positions))
+    }
+}
+
+#[inline]
+fn is_boundary(text: &[char], idx: usize) -> bool {
+    if idx == 0 {
+        return true;
+    }
+    let (prev, curr) = (text[idx - 1], text[idx]);
+    matches!(prev, '/' | '_' | '-' | '.' | ' ' | '\\' | ':')
+        || (prev.is_lowercase() && curr.is_uppercase())
+        || (!prev.is_ascii_digit() && curr.is_ascii_digit())
+}
+
+fn calc_score(text: &[char], positions: &[usize]) -> i64 {
+    let mut score = 0;
+    let mut prev: Option<usize> = None;
+    for &pos in positions {
+        score += SCORE_MATCH;
+        if is_boundary(text, pos) {
+            score += BONUS_BOUNDARY;
+        }
+        match prev {
+            Some(prev) if prev + 1 == pos => score += BONUS_CONSECUTIVE,
+            Some(prev) => score -= ((pos - prev - 1) as i64).min(PENALTY_GAP_MAX),
+            None => score -= (pos as i64).min(PENALTY_GAP_MAX),
+        }
+        prev = Some(pos);
+    }
+
+    let basename = text
+        .iter()
+        .rposition(|&ch| ch == '/')
+        .map_or(0, |idx| idx + 1);
+    if positions.first().is_some_and(|&pos| pos >= basename) {
+        score += BONUS_BASENAME;
+    }
+    // 同分时较短的文本靠前
+    score - (text.len() as i64) / 16
+}
@@ -0,0 +1,237 @@
+/// 通过inotify监听文件树中展开的目录
+///
+/// 读取事件的线程把发生变化的路径（目录中的文件，或目录本身）发送给`main`中的事件循环，
+/// 之后由文件树只重新读取受影响的目录；事件队列溢出时发送空路径，表示需要全部重新读取
+///
+/// 与`terminal/tty.rs`相同，通过内联汇编直接进行系统调用
+///
+/// 参考: https://www.man7.org/linux/man-pages/man7/inotify.7.html
+use crossbeam_channel::{bounded, never, Receiver};
+use std::{
+    arch::asm,
+    collections::HashMap,
+    ffi::{CString, OsStr},
+    fs::File,
+    io::{self, Read},
+    os::{
+        fd::{AsRawFd, FromRawFd},
+        unix::ffi::OsStrExt,
+    },
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+const IN_CLOEXEC: i64 = 0o2000000;
+
+const IN_ATTRIB: u32 = 0x4;
+const IN_CLOSE_WRITE: u32 = 0x8;
+const IN_MOVED_FROM: u32 = 0x40;
+const IN_MOVED_TO: u32 = 0x80;
+const IN_CREATE: u32 = 0x100;
+const IN_DELETE: u32 = 0x200;
+const IN_DELETE_SELF: u32 = 0x400;
+const IN_MOVE_SELF: u32 = 0x800;
+const IN_ONLYDIR: u32 = 0x1000000;
+/// 文件被删除后不再报告其事件
+const IN_EXCL_UNLINK: u32 = 0x4000000;
+const IN_Q_OVERFLOW: u32 = 0x4000;
+/// 监听被移除，包括目录被删除时
+const IN_IGNORED: u32 = 0x8000;
+
+/// 文件树关心的事件：目录中的文件增删、改名、写入与属性变化，以及目录本身被删除或移动
+const MASK: u32 = IN_ATTRIB
+    | IN_CLOSE_WRITE
+    | IN_MOVED_FROM
+    | IN_MOVED_TO
+    | IN_CREATE
+    | IN_DELETE
+    | IN_DELETE_SELF
+    | IN_MOVE_SELF
+    | IN_ONLYDIR
+    | IN_EXCL_UNLINK;
+
+/// `struct inotify_event`中`name`之前的长度
+const EVENT_SIZE: usize = 16;
+
+/// 监听描述符到目录的映射，与读取事件的线程共享
+type Dirs = Arc<Mutex<HashMap<i32, PathBuf>>>;
+
+#[derive(Default)]
+pub struct Watcher {
+    /// inotify实例，创建失败时为`None`，此时不监听任何目录
+    inotify: Option<Arc<File>>,
+    /// 正在监听的目录
+    watched: HashMap<PathBuf, i32>,
+    dirs: Dirs,
+    /// 收到但还没有被处理的变化
+    changed: Vec<PathBuf>,
+}
+
+impl Watcher {
+    /// 创建监听器，返回接收变化的管道
+    pub fn new() -> (Self, Receiver<PathBuf>) {
+        let (sender, receiver) = bounded(500);
+        let inotify = match inotify_init() {
+            Ok(fd) => Arc::new(unsafe { File::from_raw_fd(fd) }),
+            Err(_) => return (Watcher::default(), never()),
+        };
+        let dirs = Dirs::default();
+        let (file, thread_dirs) = (inotify.clone(), dirs.clone());
+        thread::spawn(move || {
+            let mut buf = [0u8; 4096];
+            loop {
+                // 被信号打断时重新读取，其他错误结束线程，主循环随之不再监听
+                let len = match (&*file).read(&mut buf) {
+                    Ok(len) => len,
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(_) => return,
+                };
+                for path in parse(&buf[..len], &thread_dirs) {
+                    if sender.send(path).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+        let watcher = Watcher {
+            inotify: Some(inotify),
+            dirs,
+            ..Default::default()
+        };
+        (watcher, receiver)
+    }
+
+    /// 使监听的目录与`dirs`一致：监听新的目录，不再监听之外的目录
+    pub fn sync(&mut self, dirs: &[PathBuf]) {
+        let inotify = match &self.inotify {
+            Some(inotify) => inotify.as_raw_fd(),
+            None => return,
+        };
+        // 被删除的目录的监听已被内核移除，之后以同样的路径重新创建时需要重新监听
+        let alive = self.dirs.lock().unwrap();
+        self.watched.retain(|_, wd| alive.contains_key(wd));
+        drop(alive);
+        let stale: Vec<PathBuf> = self
+            .watched
+            .keys()
+            .filter(|dir| !dirs.contains(dir))
+            .cloned()
+            .collect();
+        for dir in stale {
+            let wd = self.watched.remove(&dir).unwrap();
+            // 同一目录的不同路径共用一个监听描述符
+            if self.watched.values().all(|&other| other != wd) {
+                self.dirs.lock().unwrap().remove(&wd);
+                let _ = inotify_rm_watch(inotify, wd);
+            }
+        }
+        for dir in dirs {
+            if self.watched.contains_key(dir) {
+                continue;
+            }
+            // 达到监听数量的上限等错误时不再监听该目录
+            if let Ok(wd) = inotify_add_watch(inotify, dir, MASK) {
+                self.watched.insert(dir.clone(), wd);
+                self.dirs.lock().unwrap().insert(wd, dir.clone());
+            }
+        }
+    }
+
+    /// 记录读取线程发来的变化
+    #[inline]
+    pub fn push(&mut self, path: PathBuf) {
+        self.changed.push(path);
+    }
+
+    /// 取出所有未处理的变化
+    #[inline]
+    pub fn take(&mut self) -> Vec<PathBuf> {
+        std::mem::take(&mut self.changed)
+    }
+}
+
+/// 解析读取到的事件，返回发生变化的路径
+fn parse(mut buf: &[u8], dirs: &Dirs) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    let mut dirs = dirs.lock().unwrap();
+    while buf.len() >= EVENT_SIZE {
+        let field = |idx: usize| u32::from_ne_bytes(buf[idx..idx + 4].try_into().unwrap());
+        let (wd, mask, len) = (field(0) as i32, field(4), field(12) as usize);
+        let end = (EVENT_SIZE + len).min(buf.len());
+        // 名字以`\0`结尾并可能被填充
+        let name = &buf[EVENT_SIZE..end];
+        let name = &name[..name.iter().position(|&ch| ch == 0).unwrap_or(name.len())];
+        buf = &buf[end..];
+
+        if mask & IN_Q_OVERFLOW != 0 {
+            paths.push(PathBuf::new());
+        } else if mask & IN_IGNORED != 0 {
+            dirs.remove(&wd);
+        } else if let Some(dir) = dirs.get(&wd) {
+            // 目录本身的事件没有名字
+            if name.is_empty() {
+                paths.push(dir.clone());
+            } else {
+                paths.push(dir.join(OsStr::from_bytes(name)));
+            }
+        }
+    }
+    paths
+}
+
+/// 参考: https://www.man7.org/linux/man-pages/man2/inotify_init1.2.html
+fn inotify_init() -> io::Result<i32> {
+    let res: i64;
+    unsafe {
+        asm!(
+            "syscall",
+            inlateout("rax") 294i64 => res, // __NR_inotify_init1
+            in("rdi") IN_CLOEXEC,
+            lateout("rcx") _,
+            lateout("r11") _,
+        );
+    }
+    check(res)
+}
+
+fn inotify_add_watch(fd: i32, path: &Path, mask: u32) -> io::Result<i32> {
+    let path = CString::new(path.as_os_str().as_bytes())?;
+    let res: i64;
+    unsafe {
+        asm!(
+            "syscall",
+            inlateout("rax") 254i64 => res, // __NR_inotify_add_watch
+            in("rdi") fd as i64,
+            in("rsi") path.as_ptr(),
+            in("rdx") mask as i64,
+            lateout("rcx") _,
+            lateout("r11") _,
+        );
+    }
+    check(res)
+}
+
+fn inotify_rm_watch(fd: i32, wd: i32) -> io::Result<i32> {
+    let res: i64;
+    unsafe {
+        asm!(
+            "syscall",
+            inlateout("rax") 255i64 => res, // __NR_inotify_rm_watch
+            in("rdi") fd as i64,
+            in("rsi") wd as i64,
+            lateout("rcx") _,
+            lateout("r11") _,
+        );
+    }
+    check(res)
+}
+
+#[inline]
+fn check(res: i64) -> io::Result<i32> {
+    if res < 0 {
+        Err(io::Error::from_raw_os_error(-res as i32))
+    } else {
+        Ok(res as i32)
+    }
+}